@@ -20,8 +20,9 @@ impl std::fmt::Display for Changeset {
 pub type Changesets = Vec<Changeset>;
 
 pub fn compare(a: &Framebuffer, b: &Framebuffer) -> Changesets {
-    assert!(a.width() == b.width(), "width doesn't match");
-    assert!(a.height() == b.height(), "height doesn't match");
+    if a.width() != b.width() || a.height() != b.height() {
+        return repaint(b);
+    }
 
     let mut changesets = Vec::new();
 
@@ -34,8 +35,9 @@ pub fn compare(a: &Framebuffer, b: &Framebuffer) -> Changesets {
             use self::Changeset::*;
 
             match (cell_a, cell_b) {
-                (Cell::Empty, Cell::Filled { .. }) => {
-                    println!("add");
+                // Drawn with the wide grapheme on its left.
+                (_, Cell::Continuation) => {}
+                (Cell::Empty | Cell::Continuation, Cell::Filled { .. }) => {
                     let change = Add {
                         x: x_a,
                         y: y_a,
@@ -44,14 +46,12 @@ pub fn compare(a: &Framebuffer, b: &Framebuffer) -> Changesets {
 
                     changesets.push(change);
                 }
-                (Cell::Filled { .. }, Cell::Empty) => {
-                    println!("empty");
+                (Cell::Filled { .. } | Cell::Continuation, Cell::Empty) => {
                     let change = Remove { x: x_a, y: y_a };
                     changesets.push(change);
                 }
                 (Cell::Filled { .. }, Cell::Filled { .. }) => {
                     if cell_a != cell_b {
-                        println!("update");
                         let change = Update {
                             x: x_a,
                             y: y_a,
@@ -68,10 +68,27 @@ pub fn compare(a: &Framebuffer, b: &Framebuffer) -> Changesets {
     changesets
 }
 
+/// Changesets that redraw every cell of `fb`, used when the previous frame
+/// can't be trusted anymore, like after a resize.
+pub fn repaint(fb: &Framebuffer) -> Changesets {
+    fb.iter()
+        .filter_map(|((x, y), cell)| match cell {
+            Cell::Empty => Some(Changeset::Remove { x, y }),
+            Cell::Filled { .. } => Some(Changeset::Add {
+                x,
+                y,
+                cell: cell.clone(),
+            }),
+            Cell::Continuation => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
+    use super::super::test_util::glyph;
     use super::*;
-    use crate::terminal::{bg, fg, Color::*};
+    use crate::terminal::{bg, fg, Attribute, Attributes, Color::*};
 
     #[test]
     fn compare_same_framebuffer() {
@@ -81,18 +98,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: 'H',
+                grapheme: 'H'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         );
         fb.set(
             1,
             1,
             Cell::Filled {
-                character: 'P',
+                grapheme: 'P'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         );
 
@@ -110,18 +129,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: 'P',
+                grapheme: 'P'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         );
         fb_b.set(
             0,
             1,
             Cell::Filled {
-                character: 'H',
+                grapheme: 'H'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         );
 
@@ -131,12 +152,86 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "height doesn't match")]
-    fn cant_compare_different_size_framebuffer() {
+    fn compare_after_repeated_sets() {
+        let fb_a = Framebuffer::new(3, 1);
+        let mut fb_b = Framebuffer::new(3, 1);
+        let cell = |c: char| Cell::Filled {
+            grapheme: c.into(),
+            foreground: fg(Red),
+            background: bg(Yellow),
+            attributes: Attributes::empty(),
+        };
+
+        fb_b.set(0, 0, cell('a'));
+        fb_b.set(0, 0, cell('b'));
+
+        assert_eq!(
+            compare(&fb_a, &fb_b),
+            vec![Changeset::Add {
+                x: 0,
+                y: 0,
+                cell: cell('b'),
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_attribute_change() {
+        let mut fb_a = Framebuffer::new(2, 1);
+        let mut fb_b = Framebuffer::new(2, 1);
+        let cell = |attributes| Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Red),
+            background: bg(Yellow),
+            attributes,
+        };
+
+        fb_a.set(0, 0, cell(Attributes::empty()));
+        fb_b.set(0, 0, cell(Attribute::Bold | Attribute::Italic));
+
+        assert_eq!(
+            compare(&fb_a, &fb_b),
+            vec![Changeset::Update {
+                x: 0,
+                y: 0,
+                cell: cell(Attribute::Bold | Attribute::Italic),
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_different_size_framebuffer() {
         let fb_a = Framebuffer::new(2, 4);
-        let fb_b = Framebuffer::new(2, 5);
+        let mut fb_b = Framebuffer::new(2, 5);
+
+        fb_b.set(
+            1,
+            4,
+            Cell::Filled {
+                grapheme: 'P'.into(),
+                foreground: fg(Red),
+                background: bg(Yellow),
+                attributes: Attributes::empty(),
+            },
+        );
 
-        compare(&fb_a, &fb_b);
+        let diff = compare(&fb_a, &fb_b);
+
+        assert_eq!(diff.len(), 10);
+        assert_eq!(diff[0], Changeset::Remove { x: 0, y: 0 });
+        assert_eq!(
+            diff[9],
+            Changeset::Add {
+                x: 1,
+                y: 4,
+                cell: Cell::Filled {
+                    grapheme: 'P'.into(),
+                    foreground: fg(Red),
+                    background: bg(Yellow),
+                    attributes: Attributes::empty(),
+                }
+            }
+        );
     }
 
     #[test]
@@ -146,18 +241,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: 'P',
+                grapheme: 'P'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         );
         fb_a.set(
             1,
             1,
             Cell::Filled {
-                character: 'o',
+                grapheme: 'o'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         ); // remove
 
@@ -166,18 +263,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: '!',
+                grapheme: '!'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         ); // up
         fb_b.set(
             1,
             3,
             Cell::Filled {
-                character: '1',
+                grapheme: '1'.into(),
                 foreground: fg(Red),
                 background: bg(Yellow),
+                attributes: Attributes::empty(),
             },
         ); // add
 
@@ -190,9 +289,10 @@ mod test {
                     x: 0,
                     y: 0,
                     cell: Cell::Filled {
-                        character: '!',
+                        grapheme: '!'.into(),
                         foreground: fg(Red),
                         background: bg(Yellow),
+                        attributes: Attributes::empty(),
                     }
                 },
                 Changeset::Remove { x: 1, y: 1 },
@@ -200,12 +300,63 @@ mod test {
                     x: 1,
                     y: 3,
                     cell: Cell::Filled {
-                        character: '1',
+                        grapheme: '1'.into(),
                         foreground: fg(Red),
                         background: bg(Yellow),
+                        attributes: Attributes::empty(),
                     }
                 }
             ],
         );
     }
+
+    #[test]
+    fn compare_wide_graphemes() {
+        let mut a = Framebuffer::new(4, 1);
+        a.set(0, 0, glyph("a"));
+        a.set(1, 0, glyph("b"));
+
+        let mut b = a.clone();
+        b.set(0, 0, glyph("中"));
+        assert_eq!(
+            compare(&a, &b),
+            vec![Changeset::Update {
+                x: 0,
+                y: 0,
+                cell: glyph("中")
+            }]
+        );
+
+        let mut c = b.clone();
+        c.set(1, 0, glyph("c"));
+        assert_eq!(
+            compare(&b, &c),
+            vec![
+                Changeset::Remove { x: 0, y: 0 },
+                Changeset::Add {
+                    x: 1,
+                    y: 0,
+                    cell: glyph("c")
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn repaint_skips_continuations() {
+        let mut fb = Framebuffer::new(3, 1);
+        fb.set(0, 0, glyph("中"));
+
+        assert_eq!(
+            repaint(&fb),
+            vec![
+                Changeset::Add {
+                    x: 0,
+                    y: 0,
+                    cell: glyph("中")
+                },
+                Changeset::Remove { x: 2, y: 0 }
+            ]
+        );
+    }
 }
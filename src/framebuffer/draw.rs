@@ -0,0 +1,434 @@
+use super::{Cell, CellStyle, Framebuffer, Grapheme, Rect};
+
+/// Characters used to draw lines and boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum Border {
+    /// `+`, `-` and `|`.
+    Ascii,
+    Single,
+    Double,
+    /// Single lines with rounded corners.
+    Rounded,
+    Heavy,
+}
+
+// Arms of a line-drawing glyph, the directions its lines go from the center
+// of the cell.
+const UP: u8 = 1;
+const RIGHT: u8 = 2;
+const DOWN: u8 = 4;
+const LEFT: u8 = 8;
+
+/// Borders drawn with box-drawing characters, their glyphs join each other.
+const BOX_DRAWING: [Border; 4] = [
+    Border::Single,
+    Border::Double,
+    Border::Rounded,
+    Border::Heavy,
+];
+
+impl Border {
+    /// Glyphs indexed by their arms, a single arm is drawn as a whole line.
+    fn glyphs(self) -> &'static [char; 16] {
+        match self {
+            Border::Ascii => &[
+                ' ', '|', '-', '+', '|', '|', '+', '+', '-', '+', '-', '+', '+', '+', '+', '+',
+            ],
+            Border::Single => &[
+                ' ', '│', '─', '└', '│', '│', '┌', '├', '─', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+            ],
+            Border::Double => &[
+                ' ', '║', '═', '╚', '║', '║', '╔', '╠', '═', '╝', '═', '╩', '╗', '╣', '╦', '╬',
+            ],
+            Border::Rounded => &[
+                ' ', '│', '─', '╰', '│', '│', '╭', '├', '─', '╯', '─', '┴', '╮', '┤', '┬', '┼',
+            ],
+            Border::Heavy => &[
+                ' ', '┃', '━', '┗', '┃', '┃', '┏', '┣', '━', '┛', '━', '┻', '┓', '┫', '┳', '╋',
+            ],
+        }
+    }
+
+    fn glyph(self, arms: u8) -> char {
+        self.glyphs()[arms as usize]
+    }
+}
+
+/// Arms of a line-drawing glyph that `border` can join, a whole line has
+/// both arms. ASCII glyphs are common in text so they only join ASCII lines.
+fn arms(c: char, border: Border) -> Option<u8> {
+    if c == ' ' {
+        return None;
+    }
+
+    let borders: &[Border] = match border {
+        Border::Ascii => &[Border::Ascii],
+        _ => &BOX_DRAWING,
+    };
+    borders.iter().find_map(|border| {
+        (1..16u8)
+            .filter(|&arms| border.glyph(arms) == c)
+            .max_by_key(|arms| arms.count_ones())
+    })
+}
+
+impl Framebuffer {
+    /// Set every cell of the rectangle to `cell`, the rectangle is clipped
+    /// to the buffer.
+    #[allow(unused)]
+    pub fn fill(&mut self, x: u16, y: u16, width: u16, height: u16, cell: Cell) {
+        let area = Rect::new(x, y, width, height).intersection(self.area());
+        self.fill_area(self.area(), area, cell);
+    }
+
+    /// Fill `area`, which is within `clip`, see `set_in`.
+    pub(super) fn fill_area(&mut self, clip: Rect, area: Rect, cell: Cell) {
+        let step = match &cell {
+            Cell::Filled { grapheme, .. } if grapheme.is_wide() => 2,
+            _ => 1,
+        };
+        let right = area.right();
+
+        for row in area.y..area.bottom() {
+            for column in (area.x..right).step_by(step) {
+                // Don't let a wide grapheme go past the rectangle.
+                if column + step as u16 > right {
+                    self.set_in(clip, column, row, Cell::Empty);
+                    continue;
+                }
+
+                self.set_in(clip, column, row, cell.clone());
+            }
+        }
+    }
+
+    /// Draw a line of `length` cells to the right of (x, y), it joins the
+    /// lines already drawn where it crosses or ends on them.
+    #[allow(unused)]
+    pub fn horizontal_line(
+        &mut self,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        self.horizontal_line_in(self.area(), x, y, length, border, style);
+    }
+
+    pub(super) fn horizontal_line_in(
+        &mut self,
+        clip: Rect,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        for i in 0..length {
+            let arms = end_arms(i, length, LEFT, RIGHT);
+            self.join(clip, x.saturating_add(i), y, arms, border, style);
+        }
+    }
+
+    /// Draw a line of `length` cells down from (x, y), see
+    /// `horizontal_line`.
+    #[allow(unused)]
+    pub fn vertical_line(
+        &mut self,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        self.vertical_line_in(self.area(), x, y, length, border, style);
+    }
+
+    pub(super) fn vertical_line_in(
+        &mut self,
+        clip: Rect,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        for i in 0..length {
+            let arms = end_arms(i, length, UP, DOWN);
+            self.join(clip, x, y.saturating_add(i), arms, border, style);
+        }
+    }
+
+    /// Draw the outline of a `width` x `height` box from (x, y), its edges
+    /// join the lines already drawn, so adjacent boxes share their borders.
+    /// A box one cell high or wide is a line.
+    pub fn draw_box(
+        &mut self,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        let area = Rect::new(x, y, width, height);
+        self.draw_box_in(self.area(), area, border, style);
+    }
+
+    pub(super) fn draw_box_in(
+        &mut self,
+        clip: Rect,
+        area: Rect,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = area;
+
+        if area.is_empty() {
+            return;
+        }
+
+        for j in 0..height {
+            for i in 0..width {
+                let mut arms = 0;
+
+                if (j == 0 || j == height - 1) && (width > 1 || height == 1) {
+                    arms |= end_arms(i, width, LEFT, RIGHT);
+                }
+                if (i == 0 || i == width - 1) && (height > 1 || width == 1) {
+                    arms |= end_arms(j, height, UP, DOWN);
+                }
+
+                if arms != 0 {
+                    let (x, y) = (x.saturating_add(i), y.saturating_add(j));
+                    self.join(clip, x, y, arms, border, style);
+                }
+            }
+        }
+    }
+
+    /// Add `arms` to the line-drawing glyph at (x, y), or replace the cell
+    /// if it holds anything else. Cells outside of `clip` are left as is.
+    fn join(&mut self, clip: Rect, x: u16, y: u16, arms: u8, border: Border, style: &CellStyle) {
+        if !clip.contains(x, y) {
+            return;
+        }
+
+        let glyph = match self.try_get(x, y) {
+            None => return,
+            Some(Cell::Filled { grapheme, .. }) => {
+                let mut chars = grapheme.as_str().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => self::arms(c, border),
+                    _ => None,
+                }
+            }
+            Some(_) => None,
+        };
+
+        // The arms recorded by the previous join are exact, the glyph only
+        // tells them for glyphs written some other way.
+        let idx = self.idx(x, y);
+        let existing = match (glyph, self.arms[idx]) {
+            (None, _) => 0,
+            (Some(glyph), 0) => glyph,
+            (Some(_), recorded) => recorded,
+        };
+
+        let arms = existing | arms;
+        self.set_in(
+            clip,
+            x,
+            y,
+            Cell::styled(Grapheme::from(border.glyph(arms)), style),
+        );
+        self.arms[idx] = arms;
+    }
+}
+
+/// Arms of the cell `i` of a line of `length` cells going from `start` to
+/// `end`, the ends only go inwards.
+fn end_arms(i: u16, length: u16, start: u8, end: u8) -> u8 {
+    match length {
+        1 => start | end,
+        _ => {
+            let mut arms = 0;
+            if i > 0 {
+                arms |= start;
+            }
+            if i + 1 < length {
+                arms |= end;
+            }
+            arms
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_util::{glyph, text};
+    use super::*;
+
+    #[test]
+    fn fill() {
+        let mut fb = Framebuffer::new(4, 3);
+        fb.fill(1, 1, 2, 5, glyph("x"));
+        fb.fill(3, 0, 10, 1, glyph("y"));
+
+        assert_eq!(text(&fb), vec!["...y", ".xx.", ".xx."]);
+    }
+
+    #[test]
+    fn fill_with_wide_graphemes() {
+        let mut fb = Framebuffer::new(6, 1);
+        fb.fill(0, 0, 5, 1, glyph("中"));
+
+        assert_eq!(text(&fb), vec!["中中.."]);
+        assert_eq!(*fb.get(4, 0), Cell::Empty);
+    }
+
+    #[test]
+    fn lines() {
+        let mut fb = Framebuffer::new(5, 3);
+        let style = CellStyle::default();
+        fb.horizontal_line(0, 1, 5, Border::Single, &style);
+        fb.vertical_line(2, 0, 3, Border::Single, &style);
+
+        assert_eq!(text(&fb), vec!["..│..", "──┼──", "..│.."]);
+    }
+
+    #[test]
+    fn lines_ending_on_lines() {
+        let mut fb = Framebuffer::new(5, 3);
+        let style = CellStyle::default();
+        fb.vertical_line(0, 0, 3, Border::Heavy, &style);
+        fb.vertical_line(4, 0, 3, Border::Heavy, &style);
+        fb.horizontal_line(0, 1, 5, Border::Heavy, &style);
+
+        assert_eq!(text(&fb), vec!["┃...┃", "┣━━━┫", "┃...┃"]);
+    }
+
+    #[test]
+    fn lines_ending_on_lines_drawn_after() {
+        let mut fb = Framebuffer::new(5, 3);
+        let style = CellStyle::default();
+        fb.horizontal_line(0, 1, 5, Border::Heavy, &style);
+        fb.vertical_line(0, 0, 3, Border::Heavy, &style);
+        fb.vertical_line(4, 0, 3, Border::Heavy, &style);
+
+        assert_eq!(text(&fb), vec!["┃...┃", "┣━━━┫", "┃...┃"]);
+    }
+
+    #[test]
+    fn separator_drawn_before_its_box() {
+        let mut fb = Framebuffer::new(5, 4);
+        let style = CellStyle::default();
+        fb.horizontal_line(0, 2, 5, Border::Single, &style);
+        fb.draw_box(0, 0, 5, 4, Border::Single, &style);
+
+        assert_eq!(text(&fb), vec!["┌───┐", "│...│", "├───┤", "└───┘"]);
+    }
+
+    #[test]
+    fn lines_are_clipped() {
+        let mut fb = Framebuffer::new(3, 2);
+        let style = CellStyle::default();
+        fb.horizontal_line(1, 0, 10, Border::Double, &style);
+        fb.vertical_line(5, 0, 10, Border::Double, &style);
+        fb.horizontal_line(0, 1, 1, Border::Ascii, &style);
+
+        assert_eq!(text(&fb), vec![".══", "-.."]);
+    }
+
+    #[test]
+    fn border_sets() {
+        let boxes = [
+            (Border::Ascii, ["+-+", "|.|", "+-+"]),
+            (Border::Single, ["┌─┐", "│.│", "└─┘"]),
+            (Border::Double, ["╔═╗", "║.║", "╚═╝"]),
+            (Border::Rounded, ["╭─╮", "│.│", "╰─╯"]),
+            (Border::Heavy, ["┏━┓", "┃.┃", "┗━┛"]),
+        ];
+
+        for (border, expected) in boxes {
+            let mut fb = Framebuffer::new(3, 3);
+            fb.draw_box(0, 0, 3, 3, border, &CellStyle::default());
+
+            assert_eq!(text(&fb), expected.to_vec());
+        }
+    }
+
+    #[test]
+    fn adjacent_boxes_merge() {
+        let mut fb = Framebuffer::new(5, 5);
+        let style = CellStyle::default();
+        fb.draw_box(0, 0, 3, 3, Border::Single, &style);
+        fb.draw_box(2, 0, 3, 3, Border::Single, &style);
+        fb.draw_box(0, 2, 3, 3, Border::Single, &style);
+        fb.draw_box(2, 2, 3, 3, Border::Single, &style);
+
+        assert_eq!(text(&fb), vec!["┌─┬─┐", "│.│.│", "├─┼─┤", "│.│.│", "└─┴─┘"]);
+    }
+
+    #[test]
+    fn rounded_corners_become_junctions() {
+        let mut fb = Framebuffer::new(5, 3);
+        let style = CellStyle::default();
+        fb.draw_box(0, 0, 3, 3, Border::Rounded, &style);
+        fb.draw_box(2, 0, 3, 3, Border::Rounded, &style);
+
+        assert_eq!(text(&fb), vec!["╭─┬─╮", "│.│.│", "╰─┴─╯"]);
+    }
+
+    #[test]
+    fn degenerate_boxes() {
+        let mut fb = Framebuffer::new(3, 3);
+        let style = CellStyle::default();
+        fb.draw_box(0, 0, 3, 1, Border::Single, &style);
+        fb.draw_box(0, 1, 1, 2, Border::Single, &style);
+        fb.draw_box(2, 2, 1, 1, Border::Single, &style);
+        fb.draw_box(1, 1, 0, 5, Border::Single, &style);
+
+        assert_eq!(text(&fb), vec!["───", "│..", "│.┼"]);
+    }
+
+    #[test]
+    fn boxes_replace_other_content() {
+        let mut fb = Framebuffer::new(3, 2);
+        fb.fill(0, 0, 3, 2, glyph("x"));
+        fb.draw_box(0, 0, 3, 2, Border::Ascii, &CellStyle::default());
+
+        assert_eq!(text(&fb), vec!["+-+", "+-+"]);
+    }
+
+    #[test]
+    fn lines_replace_ascii_text() {
+        let mut fb = Framebuffer::new(4, 3);
+        let style = CellStyle::default();
+        fb.put_str(0, 1, "a-+b", &style);
+        fb.vertical_line(1, 0, 3, Border::Single, &style);
+        fb.vertical_line(2, 0, 3, Border::Single, &style);
+
+        assert_eq!(text(&fb), vec![".││.", "a││b", ".││."]);
+    }
+
+    #[test]
+    fn lines_of_different_sets() {
+        let mut fb = Framebuffer::new(5, 3);
+        let style = CellStyle::default();
+        fb.horizontal_line(0, 1, 5, Border::Double, &style);
+        fb.vertical_line(2, 0, 3, Border::Single, &style);
+        fb.vertical_line(0, 0, 3, Border::Ascii, &style);
+        fb.horizontal_line(3, 0, 2, Border::Ascii, &style);
+        fb.horizontal_line(3, 0, 2, Border::Heavy, &style);
+
+        assert_eq!(text(&fb), vec!["|.│━━", "|═┼══", "|.│.."]);
+    }
+}
@@ -0,0 +1,93 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Grapheme cluster held by a cell, what the user perceives as a single
+/// character, like `e` followed by a combining accent or an emoji sequence.
+///
+/// Its width is the number of columns it takes on the terminal: 2 for wide
+/// characters like CJK ideographs and most emoji, 0 for clusters without a
+/// visible base, like a lone combining mark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grapheme {
+    text: String,
+    width: u8,
+}
+
+#[allow(unused)]
+impl Grapheme {
+    /// `text` is expected to be a single grapheme cluster, see `split` to
+    /// break a string.
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+            width: text.width().min(2) as u8,
+        }
+    }
+
+    /// Grapheme clusters of `text`, in order.
+    pub fn split(text: &str) -> impl Iterator<Item = Grapheme> + '_ {
+        text.graphemes(true).map(Grapheme::new)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Columns taken on the terminal, 0, 1 or 2.
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn is_wide(&self) -> bool {
+        self.width == 2
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(c: char) -> Self {
+        Grapheme::new(c.encode_utf8(&mut [0; 4]))
+    }
+}
+
+impl From<&str> for Grapheme {
+    fn from(text: &str) -> Self {
+        Grapheme::new(text)
+    }
+}
+
+impl std::fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(Grapheme::from('a').width(), 1);
+        assert_eq!(Grapheme::from('中').width(), 2);
+        assert_eq!(Grapheme::from('😀').width(), 2);
+        assert_eq!(Grapheme::from("e\u{301}").width(), 1);
+        assert_eq!(Grapheme::from("\u{301}").width(), 0);
+        assert_eq!(Grapheme::from("👨\u{200D}👩\u{200D}👧").width(), 2);
+        assert!(Grapheme::from('中').is_wide());
+        assert!(!Grapheme::from('a').is_wide());
+    }
+
+    #[test]
+    fn split() {
+        let graphemes = Grapheme::split("ae\u{301}中🇫🇷").collect::<Vec<_>>();
+
+        assert_eq!(
+            graphemes.iter().map(Grapheme::as_str).collect::<Vec<_>>(),
+            vec!["a", "e\u{301}", "中", "🇫🇷"]
+        );
+        assert_eq!(
+            graphemes.iter().map(Grapheme::width).collect::<Vec<_>>(),
+            vec![1, 1, 2, 2]
+        );
+    }
+}
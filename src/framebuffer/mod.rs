@@ -1,13 +1,28 @@
 use std::{convert::TryInto, fmt::Write};
 
-use crate::terminal::Style;
+use crate::terminal::{Attributes, Style};
 
 pub mod diff;
+mod draw;
+mod grapheme;
+mod rect;
+mod view;
+
+pub use self::draw::Border;
+pub use self::grapheme::Grapheme;
+pub use self::rect::Rect;
+pub use self::view::{FramebufferView, FramebufferViewMut};
 
 #[derive(Debug, Clone)]
 #[allow(unused)]
 pub enum FramebufferErr {
     Writing(String),
+    OutOfBounds {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
 }
 
 impl std::error::Error for FramebufferErr {}
@@ -16,6 +31,16 @@ impl std::fmt::Display for FramebufferErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FramebufferErr::Writing(err) => write!(f, "can't write to buffer, error: {}", err),
+            FramebufferErr::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "({}, {}) is out of bound of {}x{} buffer",
+                x, y, width, height
+            ),
         }
     }
 }
@@ -24,10 +49,44 @@ impl std::fmt::Display for FramebufferErr {
 pub enum Cell {
     Empty,
     Filled {
-        character: char,
+        grapheme: Grapheme,
         foreground: Style,
         background: Style,
+        attributes: Attributes,
     },
+    /// Right half of the wide grapheme in the cell on its left, it's drawn
+    /// with it. Continuation cells are managed by `Framebuffer::set`.
+    Continuation,
+}
+
+impl Cell {
+    /// Cell drawing `grapheme` with `style`.
+    pub fn styled(grapheme: Grapheme, style: &CellStyle) -> Self {
+        Cell::Filled {
+            grapheme,
+            foreground: style.foreground.clone(),
+            background: style.background.clone(),
+            attributes: style.attributes,
+        }
+    }
+}
+
+/// Colours and attributes of the cells written by `Framebuffer::put_str`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellStyle {
+    pub foreground: Style,
+    pub background: Style,
+    pub attributes: Attributes,
+}
+
+impl Default for CellStyle {
+    fn default() -> Self {
+        Self {
+            foreground: Style::Reset,
+            background: Style::Reset,
+            attributes: Attributes::empty(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -35,22 +94,37 @@ pub struct Framebuffer {
     width: u16,
     height: u16,
     pub buf: Vec<Cell>,
+    /// Arms of the line-drawing glyphs drawn with `join`, the glyph of a
+    /// line's end doesn't show that it only goes one way. Reset by `set`.
+    arms: Vec<u8>,
 }
 
 impl Framebuffer {
     pub fn new(width: u16, height: u16) -> Self {
         const EMPTY: Cell = Cell::Empty;
 
-        let capacity = width * height;
-        let buf = vec![EMPTY; capacity.into()];
+        let capacity = width as usize * height as usize;
+        let buf = vec![EMPTY; capacity];
 
-        Self { width, height, buf }
+        Self {
+            width,
+            height,
+            buf,
+            arms: vec![0; capacity],
+        }
     }
 
     fn idx(&self, x: u16, y: u16) -> usize {
-        (y * self.width + x).into()
+        y as usize * self.width as usize + x as usize
     }
 
+    /// Set the cell at (x, y), a wide grapheme also takes the cell on its
+    /// right, as a `Cell::Continuation`. A wide grapheme that is partly
+    /// overwritten is removed, its other half becomes `Cell::Empty`.
+    ///
+    /// A wide grapheme in the last column has no room for its right half and
+    /// is replaced by a space, setting a `Cell::Continuation` sets an empty
+    /// cell.
     pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
         assert!(
             x < self.width,
@@ -65,7 +139,166 @@ impl Framebuffer {
             self.height
         );
 
-        self.buf.insert(self.idx(x, y), cell)
+        self.set_in(self.area(), x, y, cell);
+    }
+
+    /// Like `set`, with (x, y) within `clip`, the edges of `clip` are used
+    /// as the edges of the buffer. A wide grapheme that crosses them only
+    /// loses its half within `clip`, the cells outside are left as is.
+    fn set_in(&mut self, clip: Rect, x: u16, y: u16, cell: Cell) {
+        let mut cell = match cell {
+            Cell::Continuation => Cell::Empty,
+            cell => cell,
+        };
+
+        self.break_wide(clip, x, y);
+        if is_wide(&cell) {
+            match x + 1 < clip.right() {
+                true => {
+                    self.break_wide(clip, x + 1, y);
+                    let idx = self.idx(x + 1, y);
+                    self.buf[idx] = Cell::Continuation;
+                    self.arms[idx] = 0;
+                }
+                false => blank(&mut cell),
+            }
+        }
+
+        let idx = self.idx(x, y);
+        self.buf[idx] = cell;
+        self.arms[idx] = 0;
+    }
+
+    /// Empty the other half of the wide grapheme (x, y) is part of, when
+    /// it's within `clip`.
+    fn break_wide(&mut self, clip: Rect, x: u16, y: u16) {
+        let idx = self.idx(x, y);
+
+        match &self.buf[idx] {
+            Cell::Continuation if x > clip.x => self.buf[idx - 1] = Cell::Empty,
+            cell if is_wide(cell) && x + 1 < clip.right() => self.buf[idx + 1] = Cell::Empty,
+            _ => {}
+        }
+    }
+
+    /// Like `set`, but return an error instead of panicking when (x, y) is
+    /// out of bound.
+    #[allow(unused)]
+    pub fn try_set(&mut self, x: u16, y: u16, cell: Cell) -> Result<(), FramebufferErr> {
+        if !self.contains(x, y) {
+            return Err(FramebufferErr::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        self.set(x, y, cell);
+
+        Ok(())
+    }
+
+    /// Like `set`, but silently drop the cell when (x, y) is out of bound,
+    /// so drawing can go past the edges of the buffer.
+    #[allow(unused)]
+    pub fn set_clipped(&mut self, x: u16, y: u16, cell: Cell) {
+        if self.contains(x, y) {
+            self.set(x, y, cell);
+        }
+    }
+
+    /// Write `text` from (x, y) to the right, one grapheme cluster per cell
+    /// or two for wide ones, and return the number of columns written.
+    ///
+    /// The text is clipped at the right edge, a wide grapheme that doesn't
+    /// fit is dropped. Nothing is written when (x, y) is out of bound.
+    /// Control characters, like newlines, are skipped.
+    #[allow(unused)]
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, style: &CellStyle) -> u16 {
+        self.put_graphemes(self.area(), x, y, text, style, None)
+    }
+
+    /// Like `put_str`, but end the text with `…` when it's clipped.
+    #[allow(unused)]
+    pub fn put_str_ellipsis(&mut self, x: u16, y: u16, text: &str, style: &CellStyle) -> u16 {
+        self.put_graphemes(self.area(), x, y, text, style, Some(Grapheme::from('…')))
+    }
+
+    /// Write the graphemes of `text`, clipped to `clip`.
+    fn put_graphemes(
+        &mut self,
+        clip: Rect,
+        x: u16,
+        y: u16,
+        text: &str,
+        style: &CellStyle,
+        ellipsis: Option<Grapheme>,
+    ) -> u16 {
+        if !clip.contains(x, y) {
+            return 0;
+        }
+
+        let graphemes = Grapheme::split(text)
+            .filter(|g| !g.as_str().chars().any(char::is_control))
+            .collect::<Vec<_>>();
+        let width = graphemes.iter().map(columns).sum::<usize>();
+
+        let available = clip.right() - x;
+        let (limit, ellipsis) = match ellipsis {
+            Some(ellipsis) if width > available as usize => (available - 1, Some(ellipsis)),
+            _ => (available, None),
+        };
+
+        let mut written = 0;
+        for grapheme in graphemes {
+            let n = columns(&grapheme) as u16;
+            if written + n > limit {
+                break;
+            }
+
+            self.set_in(clip, x + written, y, Cell::styled(grapheme, style));
+            written += n;
+        }
+
+        if let Some(ellipsis) = ellipsis {
+            self.set_in(clip, x + written, y, Cell::styled(ellipsis, style));
+            written += 1;
+        }
+
+        written
+    }
+
+    /// Like `get`, but return `None` when (x, y) is out of bound.
+    #[allow(unused)]
+    pub fn try_get(&self, x: u16, y: u16) -> Option<&Cell> {
+        match self.contains(x, y) {
+            true => Some(self.get(x, y)),
+            false => None,
+        }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Region covering the whole buffer.
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// Read-only view of `area`, clipped to the buffer.
+    #[allow(unused)]
+    pub fn view(&self, area: Rect) -> FramebufferView {
+        FramebufferView::new(self, area.intersection(self.area()))
+    }
+
+    /// View of `area`, clipped to the buffer, to draw with local
+    /// coordinates.
+    #[allow(unused)]
+    pub fn view_mut(&mut self, area: Rect) -> FramebufferViewMut {
+        let area = area.intersection(self.area());
+        FramebufferViewMut::new(self, area)
     }
 
     pub fn get(&self, x: u16, y: u16) -> &Cell {
@@ -88,19 +321,41 @@ impl Framebuffer {
         }
     }
 
+    /// Reallocate the buffer to `width` x `height`, cells that are still in
+    /// bound keep their (x, y) position and new cells are `Cell::Empty`.
     #[allow(unused)]
-    pub fn resize(&mut self, _x: u16, _y: u16) {
-        unimplemented!()
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let mut buf = vec![Cell::Empty; width as usize * height as usize];
+        let mut arms = vec![0; buf.len()];
+
+        for y in 0..height.min(self.height) {
+            for x in 0..width.min(self.width) {
+                let idx = y as usize * width as usize + x as usize;
+                buf[idx] = self.get(x, y).clone();
+                arms[idx] = self.arms[self.idx(x, y)];
+            }
+
+            // A wide grapheme can lose its right half when shrinking.
+            if width > 0 && width < self.width {
+                blank(&mut buf[y as usize * width as usize + width as usize - 1]);
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.buf = buf;
+        self.arms = arms;
     }
 
     #[allow(unused)]
     pub fn clear(&mut self) {
-        self.buf.clear()
+        self.buf.fill(Cell::Empty);
+        self.arms.fill(0);
     }
 
     #[allow(unused)]
     pub fn capacity(&self) -> usize {
-        (self.width * self.height).into()
+        self.width as usize * self.height as usize
     }
 
     #[allow(unused)]
@@ -152,6 +407,24 @@ impl<'a> Iterator for FramebufferIterator<'a> {
     }
 }
 
+/// Columns taken by a grapheme in the grid, zero-width ones take a cell.
+fn columns(grapheme: &Grapheme) -> usize {
+    grapheme.width().max(1) as usize
+}
+
+fn is_wide(cell: &Cell) -> bool {
+    matches!(cell, Cell::Filled { grapheme, .. } if grapheme.is_wide())
+}
+
+/// Replace a wide grapheme by a space with the same styles.
+fn blank(cell: &mut Cell) {
+    if let Cell::Filled { grapheme, .. } = cell {
+        if grapheme.is_wide() {
+            *grapheme = Grapheme::from(' ');
+        }
+    }
+}
+
 #[allow(unused)]
 pub fn render<W: Write>(fb: &Framebuffer, out: &mut W) -> Result<(), FramebufferErr> {
     let mut i = 0;
@@ -161,9 +434,14 @@ pub fn render<W: Write>(fb: &Framebuffer, out: &mut W) -> Result<(), Framebuffer
 
         match cell {
             Cell::Empty => write!(out, " ").map_err(|e| FramebufferErr::Writing(e.to_string()))?,
-            Cell::Filled { character, .. } => {
-                write!(out, "{}", character).map_err(|e| FramebufferErr::Writing(e.to_string()))?
+            // Zero-width graphemes are drawn over a space, like the renderer.
+            Cell::Filled { grapheme, .. } if grapheme.width() == 0 => {
+                write!(out, " {}", grapheme).map_err(|e| FramebufferErr::Writing(e.to_string()))?
             }
+            Cell::Filled { grapheme, .. } => {
+                write!(out, "{}", grapheme).map_err(|e| FramebufferErr::Writing(e.to_string()))?
+            }
+            Cell::Continuation => {}
         }
 
         if i % fb.width() == 0 {
@@ -174,8 +452,34 @@ pub fn render<W: Write>(fb: &Framebuffer, out: &mut W) -> Result<(), Framebuffer
     Ok(())
 }
 
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+
+    /// Cell of `grapheme` in the default style.
+    pub fn glyph(grapheme: &str) -> Cell {
+        Cell::styled(Grapheme::from(grapheme), &CellStyle::default())
+    }
+
+    /// Rows of the buffer, `.` for empty cells.
+    pub fn text(fb: &Framebuffer) -> Vec<String> {
+        (0..fb.height())
+            .map(|y| {
+                (0..fb.width())
+                    .map(|x| match fb.get(x, y) {
+                        Cell::Empty => String::from("."),
+                        Cell::Filled { grapheme, .. } => grapheme.to_string(),
+                        Cell::Continuation => String::new(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use super::test_util::{glyph, text};
     use super::*;
     use crate::terminal::{bg, fg, Color::*};
 
@@ -183,15 +487,123 @@ mod test {
     fn set_and_get() {
         let mut fb = Framebuffer::new(3, 4);
         let cell = Cell::Filled {
-            character: 'a',
+            grapheme: 'a'.into(),
             foreground: fg(Green),
             background: bg(Red),
+            attributes: Attributes::empty(),
         };
         fb.set(1, 1, cell.clone());
         let c = fb.get(1, 1);
         assert_eq!(cell, *c);
     }
 
+    #[test]
+    fn set_overwrites() {
+        let mut fb = Framebuffer::new(3, 2);
+        let cell = |c: char| Cell::Filled {
+            grapheme: c.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+
+        fb.set(1, 0, cell('a'));
+        fb.set(1, 0, cell('b'));
+        fb.set(1, 0, cell('c'));
+        assert_eq!(fb.buf.len(), fb.capacity());
+        assert_eq!(*fb.get(1, 0), cell('c'));
+
+        fb.set(0, 0, cell('d'));
+        fb.set(2, 0, cell('e'));
+        fb.set(0, 1, cell('f'));
+        assert_eq!(fb.buf.len(), fb.capacity());
+
+        assert_eq!(
+            fb.iter().collect::<Vec<_>>(),
+            vec![
+                ((0, 0), &cell('d')),
+                ((1, 0), &cell('c')),
+                ((2, 0), &cell('e')),
+                ((0, 1), &cell('f')),
+                ((1, 1), &Cell::Empty),
+                ((2, 1), &Cell::Empty),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_after_clear() {
+        let mut fb = Framebuffer::new(2, 2);
+        let cell = Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+
+        fb.set(0, 0, cell.clone());
+        fb.clear();
+        assert_eq!(fb.buf.len(), fb.capacity());
+
+        fb.set(1, 1, cell.clone());
+        assert_eq!(*fb.get(0, 0), Cell::Empty);
+        assert_eq!(*fb.get(1, 1), cell);
+        assert_eq!(fb.buf.len(), fb.capacity());
+    }
+
+    #[test]
+    fn try_set_and_try_get() {
+        let mut fb = Framebuffer::new(3, 4);
+        let cell = Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+
+        assert!(fb.try_set(2, 3, cell.clone()).is_ok());
+        assert_eq!(fb.try_get(2, 3), Some(&cell));
+
+        assert!(matches!(
+            fb.try_set(3, 0, cell.clone()),
+            Err(FramebufferErr::OutOfBounds {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 4
+            })
+        ));
+        assert!(fb.try_set(0, 4, cell).is_err());
+        assert_eq!(fb.try_get(3, 0), None);
+        assert_eq!(fb.try_get(0, 4), None);
+    }
+
+    #[test]
+    fn set_clipped() {
+        let mut fb = Framebuffer::new(2, 2);
+        let cell = Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+
+        fb.set_clipped(1, 1, cell.clone());
+        fb.set_clipped(2, 1, cell.clone());
+        fb.set_clipped(1, 2, cell.clone());
+        fb.set_clipped(u16::MAX, u16::MAX, cell.clone());
+
+        assert_eq!(
+            fb.iter().collect::<Vec<_>>(),
+            vec![
+                ((0, 0), &Cell::Empty),
+                ((1, 0), &Cell::Empty),
+                ((0, 1), &Cell::Empty),
+                ((1, 1), &cell),
+            ]
+        );
+    }
+
     #[test]
     fn capacity() {
         let fb = Framebuffer::new(3, 4);
@@ -202,9 +614,10 @@ mod test {
     fn clear() {
         let mut fb = Framebuffer::new(3, 4);
         let cell = Cell::Filled {
-            character: 'a',
+            grapheme: 'a'.into(),
             foreground: fg(Green),
             background: bg(Red),
+            attributes: Attributes::empty(),
         };
         fb.set(1, 1, cell.clone());
         let c = fb.get(1, 1);
@@ -214,6 +627,71 @@ mod test {
         assert_eq!(*fb.get(1, 2), Cell::Empty);
     }
 
+    #[test]
+    fn resize_grow() {
+        let mut fb = Framebuffer::new(2, 2);
+        let cell = Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+        fb.set(1, 1, cell.clone());
+
+        fb.resize(4, 3);
+
+        assert_eq!(fb.width(), 4);
+        assert_eq!(fb.height(), 3);
+        assert_eq!(fb.buf.len(), fb.capacity());
+        assert_eq!(*fb.get(1, 1), cell);
+        assert_eq!(*fb.get(3, 2), Cell::Empty);
+        assert_eq!(*fb.get(0, 1), Cell::Empty);
+    }
+
+    #[test]
+    fn resize_past_u16_cells() {
+        let mut fb = Framebuffer::new(2, 2);
+        fb.resize(400, 200);
+        fb.set(399, 199, glyph("a"));
+
+        assert_eq!(fb.capacity(), 80_000);
+        assert_eq!(*fb.get(399, 199), glyph("a"));
+        assert_eq!(diff::repaint(&fb).len(), 80_000);
+        assert_eq!(Framebuffer::new(400, 200).capacity(), 80_000);
+    }
+
+    #[test]
+    fn resize_shrink() {
+        let mut fb = Framebuffer::new(4, 4);
+        let cell_1 = Cell::Filled {
+            grapheme: 'a'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+        let cell_2 = Cell::Filled {
+            grapheme: 'b'.into(),
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+        fb.set(1, 0, cell_1.clone());
+        fb.set(3, 3, cell_2);
+
+        fb.resize(2, 2);
+
+        assert_eq!(fb.buf.len(), 4);
+        assert_eq!(
+            fb.iter().collect::<Vec<_>>(),
+            vec![
+                ((0, 0), &Cell::Empty),
+                ((1, 0), &cell_1),
+                ((0, 1), &Cell::Empty),
+                ((1, 1), &Cell::Empty),
+            ]
+        );
+    }
+
     #[test]
     fn height() {
         let fb = Framebuffer::new(3, 4);
@@ -231,15 +709,17 @@ mod test {
         let mut fb = Framebuffer::new(2, 2);
 
         let cell_1 = Cell::Filled {
-            character: 'X',
+            grapheme: 'X'.into(),
             foreground: fg(Green),
             background: bg(Red),
+            attributes: Attributes::empty(),
         };
 
         let cell_2 = Cell::Filled {
-            character: 'Y',
+            grapheme: 'Y'.into(),
             foreground: fg(Green),
             background: bg(Red),
+            attributes: Attributes::empty(),
         };
 
         fb.set(0, 0, cell_1.clone());
@@ -259,18 +739,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: '!',
+                grapheme: '!'.into(),
                 foreground: fg(Green),
                 background: bg(Red),
+                attributes: Attributes::empty(),
             },
         );
         fb_a.set(
             1,
             3,
             Cell::Filled {
-                character: '1',
+                grapheme: '1'.into(),
                 foreground: fg(Green),
                 background: bg(Red),
+                attributes: Attributes::empty(),
             },
         );
 
@@ -283,9 +765,10 @@ mod test {
                 (
                     (0, 0),
                     &Cell::Filled {
-                        character: '!',
+                        grapheme: '!'.into(),
                         foreground: fg(Green),
                         background: bg(Red),
+                        attributes: Attributes::empty(),
                     }
                 ),
                 ((1, 0), &Cell::Empty),
@@ -297,9 +780,10 @@ mod test {
                 (
                     (1, 3),
                     &Cell::Filled {
-                        character: '1',
+                        grapheme: '1'.into(),
                         foreground: fg(Green),
                         background: bg(Red),
+                        attributes: Attributes::empty(),
                     }
                 ),
                 ((0, 4), &Cell::Empty),
@@ -317,18 +801,20 @@ mod test {
             0,
             0,
             Cell::Filled {
-                character: 'X',
+                grapheme: 'X'.into(),
                 foreground: fg(Green),
                 background: bg(Red),
+                attributes: Attributes::empty(),
             },
         );
         fb.set(
             1,
             1,
             Cell::Filled {
-                character: 'Y',
+                grapheme: 'Y'.into(),
                 foreground: fg(Green),
                 background: bg(Red),
+                attributes: Attributes::empty(),
             },
         );
 
@@ -337,4 +823,166 @@ mod test {
         render(&fb, &mut buf).expect("should be able to write to the buffer");
         assert_eq!(buf, String::from("X \n Y\n"));
     }
+
+    fn row(fb: &Framebuffer, y: u16) -> Vec<Cell> {
+        (0..fb.width()).map(|x| fb.get(x, y).clone()).collect()
+    }
+
+    #[test]
+    fn set_wide_grapheme() {
+        let mut fb = Framebuffer::new(4, 1);
+        fb.set(1, 0, glyph("中"));
+
+        assert_eq!(
+            row(&fb, 0),
+            vec![Cell::Empty, glyph("中"), Cell::Continuation, Cell::Empty]
+        );
+    }
+
+    #[test]
+    fn overwrite_half_of_wide_grapheme() {
+        let mut fb = Framebuffer::new(4, 1);
+
+        fb.set(1, 0, glyph("中"));
+        fb.set(1, 0, glyph("a"));
+        assert_eq!(
+            row(&fb, 0),
+            vec![Cell::Empty, glyph("a"), Cell::Empty, Cell::Empty]
+        );
+
+        fb.set(1, 0, glyph("中"));
+        fb.set(2, 0, glyph("b"));
+        assert_eq!(
+            row(&fb, 0),
+            vec![Cell::Empty, Cell::Empty, glyph("b"), Cell::Empty]
+        );
+
+        fb.set(1, 0, glyph("中"));
+        fb.set(2, 0, Cell::Empty);
+        assert_eq!(row(&fb, 0), vec![Cell::Empty; 4]);
+    }
+
+    #[test]
+    fn overlapping_wide_graphemes() {
+        let mut fb = Framebuffer::new(5, 1);
+
+        fb.set(0, 0, glyph("中"));
+        fb.set(2, 0, glyph("文"));
+        fb.set(1, 0, glyph("字"));
+
+        assert_eq!(
+            row(&fb, 0),
+            vec![
+                Cell::Empty,
+                glyph("字"),
+                Cell::Continuation,
+                Cell::Empty,
+                Cell::Empty
+            ]
+        );
+    }
+
+    #[test]
+    fn wide_grapheme_in_last_column() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.set(1, 0, glyph("中"));
+
+        assert_eq!(row(&fb, 0), vec![Cell::Empty, glyph(" ")]);
+    }
+
+    #[test]
+    fn set_continuation() {
+        let mut fb = Framebuffer::new(2, 1);
+        fb.set(0, 0, glyph("a"));
+        fb.set(0, 0, Cell::Continuation);
+
+        assert_eq!(row(&fb, 0), vec![Cell::Empty, Cell::Empty]);
+    }
+
+    #[test]
+    fn resize_cuts_wide_grapheme() {
+        let mut fb = Framebuffer::new(4, 1);
+        fb.set(0, 0, glyph("中"));
+        fb.set(2, 0, glyph("文"));
+
+        fb.resize(3, 1);
+
+        assert_eq!(
+            row(&fb, 0),
+            vec![glyph("中"), Cell::Continuation, glyph(" ")]
+        );
+    }
+
+    #[test]
+    fn render_graphemes() {
+        let mut fb = Framebuffer::new(4, 1);
+        fb.set(0, 0, glyph("中"));
+        fb.set(2, 0, glyph("e\u{301}"));
+        fb.set(3, 0, glyph("\u{301}"));
+
+        let mut buf = String::new();
+
+        render(&fb, &mut buf).expect("should be able to write to the buffer");
+        assert_eq!(buf, String::from("中e\u{301} \u{301}\n"));
+    }
+
+    #[test]
+    fn put_str() {
+        let mut fb = Framebuffer::new(8, 2);
+        let style = CellStyle {
+            foreground: fg(Green),
+            background: bg(Red),
+            attributes: Attributes::empty(),
+        };
+
+        assert_eq!(fb.put_str(1, 0, "ab中e\u{301}", &style), 5);
+        assert_eq!(text(&fb)[0], ".ab中e\u{301}..");
+        assert_eq!(*fb.get(1, 0), Cell::styled('a'.into(), &style));
+        assert_eq!(*fb.get(4, 0), Cell::Continuation);
+
+        assert_eq!(fb.put_str(0, 1, "a\tb\nc", &style), 3);
+        assert_eq!(text(&fb)[1], "abc.....");
+    }
+
+    #[test]
+    fn put_str_clips() {
+        let mut fb = Framebuffer::new(5, 1);
+        let style = CellStyle::default();
+
+        assert_eq!(fb.put_str(2, 0, "abcdef", &style), 3);
+        assert_eq!(text(&fb)[0], "..abc");
+
+        fb.clear();
+        assert_eq!(fb.put_str(2, 0, "a中文", &style), 3);
+        assert_eq!(text(&fb)[0], "..a中");
+
+        fb.clear();
+        assert_eq!(fb.put_str(3, 0, "a中", &style), 1);
+        assert_eq!(text(&fb)[0], "...a.");
+
+        assert_eq!(fb.put_str(5, 0, "a", &style), 0);
+        assert_eq!(fb.put_str(0, 1, "a", &style), 0);
+        assert_eq!(fb.put_str(0, 0, "", &style), 0);
+    }
+
+    #[test]
+    fn put_str_ellipsis() {
+        let mut fb = Framebuffer::new(5, 1);
+        let style = CellStyle::default();
+
+        assert_eq!(fb.put_str_ellipsis(0, 0, "abcde", &style), 5);
+        assert_eq!(text(&fb)[0], "abcde");
+
+        fb.clear();
+        assert_eq!(fb.put_str_ellipsis(0, 0, "abcdef", &style), 5);
+        assert_eq!(text(&fb)[0], "abcd…");
+
+        fb.clear();
+        assert_eq!(fb.put_str_ellipsis(1, 0, "ab中文", &style), 3);
+        assert_eq!(text(&fb)[0], ".ab….");
+
+        fb.clear();
+        assert_eq!(fb.put_str_ellipsis(4, 0, "ab", &style), 1);
+        assert_eq!(text(&fb)[0], "....…");
+    }
 }
@@ -0,0 +1,169 @@
+/// Rectangular region of cells, from (x, y) to the right and down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+#[allow(unused)]
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Column right after the last one of the region.
+    pub fn right(&self) -> u16 {
+        self.x.saturating_add(self.width)
+    }
+
+    /// Row right after the last one of the region.
+    pub fn bottom(&self) -> u16 {
+        self.y.saturating_add(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Region covered by both rectangles, empty when they don't overlap.
+    pub fn intersection(&self, other: Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+
+        Rect {
+            x,
+            y,
+            width: self.right().min(other.right()).saturating_sub(x),
+            height: self.bottom().min(other.bottom()).saturating_sub(y),
+        }
+    }
+
+    /// Same region moved `x` columns right and `y` rows down.
+    pub fn offset(&self, x: u16, y: u16) -> Rect {
+        Rect {
+            x: self.x.saturating_add(x),
+            y: self.y.saturating_add(y),
+            ..*self
+        }
+    }
+
+    /// Region without `margin` cells on each side, like the inside of a box.
+    pub fn inner(&self, margin: u16) -> Rect {
+        Rect {
+            x: self.x.saturating_add(margin),
+            y: self.y.saturating_add(margin),
+            width: self.width.saturating_sub(margin.saturating_mul(2)),
+            height: self.height.saturating_sub(margin.saturating_mul(2)),
+        }
+    }
+
+    /// Split into the `at` first columns and the rest.
+    pub fn split_columns(&self, at: u16) -> (Rect, Rect) {
+        let at = at.min(self.width);
+
+        (
+            Rect { width: at, ..*self },
+            Rect {
+                x: self.x.saturating_add(at),
+                width: self.width - at,
+                ..*self
+            },
+        )
+    }
+
+    /// Split into the `at` first rows and the rest.
+    pub fn split_rows(&self, at: u16) -> (Rect, Rect) {
+        let at = at.min(self.height);
+
+        (
+            Rect {
+                height: at,
+                ..*self
+            },
+            Rect {
+                y: self.y.saturating_add(at),
+                height: self.height - at,
+                ..*self
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edges_and_contains() {
+        let rect = Rect::new(2, 3, 4, 5);
+
+        assert_eq!(rect.right(), 6);
+        assert_eq!(rect.bottom(), 8);
+        assert!(rect.contains(2, 3));
+        assert!(rect.contains(5, 7));
+        assert!(!rect.contains(6, 7));
+        assert!(!rect.contains(5, 8));
+        assert!(!rect.contains(1, 3));
+        assert!(!rect.is_empty());
+        assert!(Rect::new(2, 3, 0, 5).is_empty());
+        assert!(!Rect::new(2, 3, 0, 5).contains(2, 3));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = Rect::new(0, 0, 4, 4);
+
+        assert_eq!(a.intersection(Rect::new(2, 1, 5, 2)), Rect::new(2, 1, 2, 2));
+        assert_eq!(a.intersection(Rect::new(1, 1, 1, 1)), Rect::new(1, 1, 1, 1));
+        assert!(a.intersection(Rect::new(4, 0, 2, 2)).is_empty());
+        assert!(a.intersection(Rect::new(10, 10, 2, 2)).is_empty());
+    }
+
+    #[test]
+    fn offset_and_inner() {
+        let rect = Rect::new(1, 2, 6, 4);
+
+        assert_eq!(rect.offset(3, 1), Rect::new(4, 3, 6, 4));
+        assert_eq!(rect.inner(1), Rect::new(2, 3, 4, 2));
+        assert!(rect.inner(3).is_empty());
+        assert_eq!(Rect::new(u16::MAX, 0, 1, 1).offset(1, 0).x, u16::MAX);
+    }
+
+    #[test]
+    fn splits() {
+        let rect = Rect::new(1, 2, 6, 4);
+
+        assert_eq!(
+            rect.split_columns(2),
+            (Rect::new(1, 2, 2, 4), Rect::new(3, 2, 4, 4))
+        );
+        assert_eq!(
+            rect.split_rows(3),
+            (Rect::new(1, 2, 6, 3), Rect::new(1, 5, 6, 1))
+        );
+        assert_eq!(rect.split_columns(10), (rect, Rect::new(7, 2, 0, 4)));
+    }
+
+    #[test]
+    fn splits_at_the_edge() {
+        assert_eq!(
+            Rect::new(u16::MAX, 0, 10, 1).split_columns(5),
+            (Rect::new(u16::MAX, 0, 5, 1), Rect::new(u16::MAX, 0, 5, 1))
+        );
+        assert_eq!(
+            Rect::new(0, u16::MAX, 1, 10).split_rows(5),
+            (Rect::new(0, u16::MAX, 1, 5), Rect::new(0, u16::MAX, 1, 5))
+        );
+    }
+}
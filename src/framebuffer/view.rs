@@ -0,0 +1,321 @@
+use super::{Border, Cell, CellStyle, Framebuffer, Grapheme, Rect};
+
+/// Read-only view of a region of a `Framebuffer`.
+///
+/// (0, 0) is the top left cell of the region and the cells outside of it
+/// are out of bound, so a widget can read its region without knowing where
+/// it is on the screen.
+#[derive(Debug, Clone, Copy)]
+pub struct FramebufferView<'a> {
+    fb: &'a Framebuffer,
+    area: Rect,
+}
+
+impl<'a> FramebufferView<'a> {
+    pub(super) fn new(fb: &'a Framebuffer, area: Rect) -> Self {
+        Self { fb, area }
+    }
+
+    /// Region of the view, in the coordinates of the buffer.
+    #[allow(unused)]
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    #[allow(unused)]
+    pub fn width(&self) -> u16 {
+        self.area.width
+    }
+
+    #[allow(unused)]
+    pub fn height(&self) -> u16 {
+        self.area.height
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x < self.area.width && y < self.area.height
+    }
+
+    /// Cell at (x, y) of the view, `None` when it's out of bound.
+    pub fn get(&self, x: u16, y: u16) -> Option<&'a Cell> {
+        match self.contains(x, y) {
+            true => Some(self.fb.get(self.area.x + x, self.area.y + y)),
+            false => None,
+        }
+    }
+
+    /// View of `area`, in the coordinates of this view, clipped to it.
+    #[allow(unused)]
+    pub fn view(&self, area: Rect) -> FramebufferView<'a> {
+        FramebufferView::new(self.fb, translate(self.area, area))
+    }
+}
+
+/// View of a region of a `Framebuffer` to draw with local coordinates.
+///
+/// (0, 0) is the top left cell of the region and everything drawn is
+/// clipped to it, like `Framebuffer::set_clipped`. Views can be split in
+/// nested views with `view_mut`.
+#[derive(Debug)]
+pub struct FramebufferViewMut<'a> {
+    fb: &'a mut Framebuffer,
+    area: Rect,
+}
+
+impl<'a> FramebufferViewMut<'a> {
+    pub(super) fn new(fb: &'a mut Framebuffer, area: Rect) -> Self {
+        Self { fb, area }
+    }
+
+    /// Region of the view, in the coordinates of the buffer.
+    #[allow(unused)]
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    #[allow(unused)]
+    pub fn width(&self) -> u16 {
+        self.area.width
+    }
+
+    #[allow(unused)]
+    pub fn height(&self) -> u16 {
+        self.area.height
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x < self.area.width && y < self.area.height
+    }
+
+    /// Cell at (x, y) of the view, `None` when it's out of bound.
+    #[allow(unused)]
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.as_view().get(x, y)
+    }
+
+    pub fn as_view(&self) -> FramebufferView<'_> {
+        FramebufferView::new(self.fb, self.area)
+    }
+
+    /// View of `area`, in the coordinates of this view, clipped to it.
+    #[allow(unused)]
+    pub fn view_mut(&mut self, area: Rect) -> FramebufferViewMut<'_> {
+        let area = translate(self.area, area);
+        FramebufferViewMut::new(self.fb, area)
+    }
+
+    /// Set the cell at (x, y) of the view, see `Framebuffer::set`. The cell
+    /// is dropped when (x, y) is out of bound, a wide grapheme in the last
+    /// column of the view is replaced by a space and a wide grapheme that
+    /// crosses the edges of the view keeps its half outside of it.
+    #[allow(unused)]
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if !self.contains(x, y) {
+            return;
+        }
+
+        self.fb
+            .set_in(self.area, self.area.x + x, self.area.y + y, cell);
+    }
+
+    /// Empty every cell of the view.
+    #[allow(unused)]
+    pub fn clear(&mut self) {
+        self.fb.fill_area(self.area, self.area, Cell::Empty);
+    }
+
+    #[allow(unused)]
+    pub fn fill(&mut self, x: u16, y: u16, width: u16, height: u16, cell: Cell) {
+        let area = translate(self.area, Rect::new(x, y, width, height));
+        self.fb.fill_area(self.area, area, cell);
+    }
+
+    /// Write `text` from (x, y), clipped to the view, see
+    /// `Framebuffer::put_str`.
+    #[allow(unused)]
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, style: &CellStyle) -> u16 {
+        let (x, y) = self.absolute(x, y);
+        self.fb.put_graphemes(self.area, x, y, text, style, None)
+    }
+
+    #[allow(unused)]
+    pub fn put_str_ellipsis(&mut self, x: u16, y: u16, text: &str, style: &CellStyle) -> u16 {
+        let (x, y) = self.absolute(x, y);
+        let ellipsis = Some(Grapheme::from('…'));
+        self.fb
+            .put_graphemes(self.area, x, y, text, style, ellipsis)
+    }
+
+    #[allow(unused)]
+    pub fn horizontal_line(
+        &mut self,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        let (x, y) = self.absolute(x, y);
+        self.fb
+            .horizontal_line_in(self.area, x, y, length, border, style);
+    }
+
+    #[allow(unused)]
+    pub fn vertical_line(
+        &mut self,
+        x: u16,
+        y: u16,
+        length: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        let (x, y) = self.absolute(x, y);
+        self.fb
+            .vertical_line_in(self.area, x, y, length, border, style);
+    }
+
+    #[allow(unused)]
+    pub fn draw_box(
+        &mut self,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        border: Border,
+        style: &CellStyle,
+    ) {
+        let (x, y) = self.absolute(x, y);
+        let area = Rect::new(x, y, width, height);
+        self.fb.draw_box_in(self.area, area, border, style);
+    }
+
+    /// Coordinates of the buffer of (x, y) of the view, they can be out of
+    /// the view.
+    fn absolute(&self, x: u16, y: u16) -> (u16, u16) {
+        (self.area.x.saturating_add(x), self.area.y.saturating_add(y))
+    }
+}
+
+/// Region of the buffer of `local`, a region of the view of `area`, clipped
+/// to the view.
+fn translate(area: Rect, local: Rect) -> Rect {
+    local.offset(area.x, area.y).intersection(area)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_util::{glyph, text};
+    use super::*;
+
+    #[test]
+    fn local_coordinates() {
+        let mut fb = Framebuffer::new(5, 4);
+        let mut view = fb.view_mut(Rect::new(1, 1, 3, 2));
+
+        assert_eq!(view.width(), 3);
+        assert_eq!(view.height(), 2);
+        view.set(0, 0, glyph("a"));
+        view.set(2, 1, glyph("b"));
+        assert_eq!(view.get(0, 0), Some(&glyph("a")));
+
+        assert_eq!(text(&fb), vec![".....", ".a...", "...b.", "....."]);
+    }
+
+    #[test]
+    fn writes_are_clipped() {
+        let mut fb = Framebuffer::new(5, 4);
+        let mut view = fb.view_mut(Rect::new(1, 1, 3, 2));
+
+        view.set(3, 0, glyph("x"));
+        view.set(0, 2, glyph("x"));
+        view.set(u16::MAX, u16::MAX, glyph("x"));
+        assert_eq!(view.get(3, 0), None);
+
+        assert_eq!(view.put_str(1, 0, "abcdef", &CellStyle::default()), 2);
+        assert_eq!(
+            view.put_str_ellipsis(0, 1, "abcdef", &CellStyle::default()),
+            3
+        );
+        view.set(2, 1, glyph("中"));
+
+        assert_eq!(text(&fb), vec![".....", "..ab.", ".ab .", "....."]);
+    }
+
+    #[test]
+    fn drawing_is_clipped() {
+        let mut fb = Framebuffer::new(6, 4);
+        let mut view = fb.view_mut(Rect::new(1, 1, 4, 2));
+        let style = CellStyle::default();
+
+        view.fill(2, 0, 10, 10, glyph("x"));
+        view.horizontal_line(0, 0, 10, Border::Single, &style);
+        view.draw_box(0, 0, 3, 5, Border::Ascii, &style);
+
+        assert_eq!(text(&fb), vec!["......", ".+-+─.", ".|.|x.", "......"]);
+
+        fb.view_mut(Rect::new(1, 1, 4, 2)).clear();
+        assert_eq!(text(&fb), vec!["......"; 4]);
+    }
+
+    #[test]
+    fn views_are_clipped_to_the_buffer() {
+        let mut fb = Framebuffer::new(4, 3);
+
+        assert_eq!(
+            fb.view(Rect::new(2, 1, 10, 10)).area(),
+            Rect::new(2, 1, 2, 2)
+        );
+        assert!(fb.view(Rect::new(5, 5, 2, 2)).area().is_empty());
+
+        let mut view = fb.view_mut(Rect::new(5, 5, 2, 2));
+        view.set(0, 0, glyph("x"));
+        view.put_str(0, 0, "x", &CellStyle::default());
+        assert_eq!(text(&fb), vec!["...."; 3]);
+    }
+
+    #[test]
+    fn nested_views() {
+        let mut fb = Framebuffer::new(8, 4);
+        let style = CellStyle::default();
+
+        {
+            let mut outer = fb.view_mut(Rect::new(1, 0, 6, 4));
+            outer.draw_box(0, 0, 6, 4, Border::Single, &style);
+
+            let inner = Rect::new(0, 0, 6, 4).inner(1);
+            let mut content = outer.view_mut(inner);
+            let (left, right) = Rect::new(0, 0, content.width(), content.height()).split_columns(2);
+
+            content.view_mut(left).put_str(0, 0, "abc", &style);
+            let mut right = content.view_mut(right);
+            right.put_str(0, 1, "defg", &style);
+            assert_eq!(right.area(), Rect::new(4, 1, 2, 2));
+        }
+
+        assert_eq!(
+            text(&fb),
+            vec![".┌────┐.", ".│ab..│.", ".│..de│.", ".└────┘."]
+        );
+
+        let view = fb.view(Rect::new(2, 1, 4, 2)).view(Rect::new(1, 1, 5, 5));
+        assert_eq!(view.area(), Rect::new(3, 2, 3, 1));
+        assert_eq!(view.get(1, 0), Some(&glyph("d")));
+        assert_eq!(view.get(0, 1), None);
+    }
+
+    #[test]
+    fn wide_graphemes_across_the_edges() {
+        let mut fb = Framebuffer::new(6, 1);
+        fb.set(0, 0, glyph("中"));
+        fb.set(3, 0, glyph("中"));
+
+        let mut view = fb.view_mut(Rect::new(1, 0, 3, 1));
+        view.set(0, 0, glyph("a"));
+        view.set(2, 0, glyph("b"));
+
+        assert_eq!(*fb.get(0, 0), glyph("中"));
+        assert_eq!(*fb.get(1, 0), glyph("a"));
+        assert_eq!(*fb.get(3, 0), glyph("b"));
+        assert_eq!(*fb.get(4, 0), Cell::Continuation);
+    }
+}
@@ -2,55 +2,107 @@ mod framebuffer;
 mod renderer;
 mod terminal;
 
-use framebuffer::{diff, Cell, Framebuffer};
+use std::time::Duration;
+
+use framebuffer::{diff, Border, Cell, CellStyle, Framebuffer, Grapheme};
 use renderer::{Renderer, Terminal};
-use terminal::{bg, fg, window_size, Color, Command, Style};
+use terminal::{
+    bg, fg,
+    input::{Event, Input, Key, KeyEvent},
+    window_size, Attributes, Capabilities, Color, Command, Escapes, RawMode, ResizeNotifier,
+    Session, Style, SYNCHRONIZED_OUTPUT,
+};
 
 extern crate libc;
+extern crate signal_hook;
+extern crate unicode_segmentation;
+extern crate unicode_width;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let w = window_size()?;
-    let fb = Framebuffer::new(w.cols, w.rows);
-    let mut fb_1 = fb.clone();
+fn draw(fb: &mut Framebuffer) {
+    fb.draw_box(1, 2, 3, 5, Border::Rounded, &CellStyle::default());
 
-    fb_1.set(
+    fb.set(
         2,
         3,
         Cell::Filled {
-            character: 'K',
+            grapheme: Grapheme::from('K'),
             foreground: fg(Color::White),
             background: bg(Color::Red),
+            attributes: Attributes::empty(),
         },
     );
 
-    fb_1.set(
+    fb.set(
         2,
         4,
         Cell::Filled {
-            character: 'k',
+            grapheme: Grapheme::from('k'),
             foreground: fg(Color::Black),
             background: bg(Color::Cyan),
+            attributes: Attributes::empty(),
         },
     );
-    fb_1.set(
+    fb.set(
         2,
         5,
         Cell::Filled {
-            character: '!',
+            grapheme: Grapheme::from('!'),
             foreground: fg(Color::White),
             background: bg(Color::Yellow),
+            attributes: Attributes::empty(),
         },
     );
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let w = window_size()?;
+    let mut fb = Framebuffer::new(w.cols, w.rows);
+    let mut fb_1 = fb.clone();
+    draw(&mut fb_1);
+
+    let capabilities = Capabilities::detect();
+    let escapes = Escapes::new(capabilities.terminfo.clone());
+
+    let _session = Session::new(RawMode::new()?, &escapes)?;
+    let resize = ResizeNotifier::new()?;
+    let mut input = Input::new()?;
+
+    for (sequence, key) in capabilities.keys() {
+        input.add_key(sequence, key);
+    }
 
-    let changesets = diff::compare(&fb, &fb_1);
-    println!("changesets:\n {:?}", changesets);
     let mut out = std::io::stdout();
-    let _ = terminal::configure(&mut out);
-    let mut renderer = Terminal::new(&mut out);
-    renderer.submit(changesets);
+    let synchronized = input
+        .query_mode(&mut out, SYNCHRONIZED_OUTPUT, Duration::from_millis(100))?
+        .is_supported();
+
+    let mut renderer = Terminal::new(&mut out, w.cols);
+    renderer.set_synchronized_output(synchronized);
+    renderer.set_color_depth(capabilities.color_depth);
+    renderer.set_styled_underlines(capabilities.styled_underlines);
+    renderer.set_escapes(escapes);
+
+    loop {
+        let changesets = match renderer.is_invalidated() {
+            true => diff::repaint(&fb_1),
+            false => diff::compare(&fb, &fb_1),
+        };
+        renderer.submit(changesets)?;
+        fb = fb_1.clone();
+
+        if let Some(Event::Key(KeyEvent {
+            key: Key::Char('q') | Key::Escape,
+            ..
+        })) = input.read(None)?
+        {
+            break;
+        }
 
-    // println!("\x1b[45mHoooko\x1b[0m");
-    // println!("\x1b[35mHoooko\x1b[0m");
+        if let Some(w) = resize.poll()? {
+            fb_1.resize(w.cols, w.rows);
+            renderer.resize(w.cols);
+        }
+    }
 
     Ok(())
 }
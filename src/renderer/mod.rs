@@ -3,28 +3,152 @@ use std::io::Write;
 use crate::{
     framebuffer::{
         diff::{Changeset, Changesets},
-        Cell,
+        Cell, Grapheme,
     },
-    terminal::{Command, Style},
+    terminal::{Attribute, Attributes, ColorDepth, Command, Escapes, Style},
 };
 
-pub trait Renderer {
-    fn submit(&mut self, changesets: Changesets);
+#[derive(Debug)]
+pub enum RendererErr {
+    Writing(std::io::Error),
 }
 
+impl std::error::Error for RendererErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RendererErr::Writing(err) => Some(err),
+        }
+    }
+}
+
+impl std::fmt::Display for RendererErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RendererErr::Writing(err) => write!(f, "can't write to terminal, error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for RendererErr {
+    fn from(err: std::io::Error) -> Self {
+        RendererErr::Writing(err)
+    }
+}
+
+pub trait Renderer {
+    /// Draw the changesets, when an error is returned part of the frame may
+    /// be on screen and the renderer is invalidated.
+    fn submit(&mut self, changesets: Changesets) -> Result<(), RendererErr>;
+}
 pub struct Terminal<W>
 where
     W: Write,
 {
     out: W,
+    /// Frame being assembled, written to `out` at the end of `submit`.
+    buf: Vec<u8>,
+    escapes: Escapes,
+    /// Wrap each frame in a synchronized update.
+    synchronized: bool,
+    /// The terminal supports underline styles and colours.
+    styled_underlines: bool,
+    /// Colours are converted to the nearest ones available at this depth.
+    color_depth: ColorDepth,
+    invalidated: bool,
+    width: u16,
+    /// Position of the terminal cursor, `None` when it isn't known.
+    cursor: Option<(u16, u16)>,
+    /// Styles active on the terminal, `None` when they aren't known.
+    pen: Option<Pen>,
+}
+
+/// Graphic rendition applied to the written glyphs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pen {
+    foreground: Style,
+    background: Style,
+    attributes: Attributes,
+}
+
+impl Pen {
+    fn reset() -> Self {
+        Self {
+            foreground: Style::Reset,
+            background: Style::Reset,
+            attributes: Attributes::empty(),
+        }
+    }
 }
 
 impl<T: Write> Terminal<T> {
-    pub fn new(out: T) -> Self {
-        Self { out }
+    pub fn new(out: T, width: u16) -> Self {
+        Self {
+            out,
+            buf: Vec::new(),
+            escapes: Escapes::default(),
+            synchronized: false,
+            styled_underlines: false,
+            color_depth: ColorDepth::TrueColor,
+            invalidated: false,
+            width,
+            cursor: None,
+            pen: None,
+        }
+    }
+
+    /// Clear the whole screen before drawing the next submitted frame, after
+    /// a resize the terminal content can't be trusted and the next
+    /// changesets are expected to cover every cell (see `diff::repaint`).
+    #[allow(unused)]
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+        self.cursor = None;
+    }
+
+    /// True until the next successful submit after `invalidate` or after a
+    /// failed submit, the next frame should be a full repaint.
+    #[allow(unused)]
+    pub fn is_invalidated(&self) -> bool {
+        self.invalidated
     }
 
-    fn render(&mut self, change: Changeset) {
+    /// Track the new width of the terminal and invalidate the screen.
+    #[allow(unused)]
+    pub fn resize(&mut self, width: u16) {
+        self.width = width;
+        self.invalidate();
+    }
+
+    /// Wrap each submitted frame in Begin/End Synchronized Update so the
+    /// terminal draws it at once, only enable it when the terminal supports
+    /// the mode (see `Input::query_mode`).
+    #[allow(unused)]
+    pub fn set_synchronized_output(&mut self, enabled: bool) {
+        self.synchronized = enabled;
+    }
+
+    /// Draw underline styles and colours, when disabled they fall back to a
+    /// plain underline.
+    #[allow(unused)]
+    pub fn set_styled_underlines(&mut self, enabled: bool) {
+        self.styled_underlines = enabled;
+    }
+
+    /// Encode the commands with the sequences of a terminfo entry instead
+    /// of the built-in ones.
+    #[allow(unused)]
+    pub fn set_escapes(&mut self, escapes: Escapes) {
+        self.escapes = escapes;
+    }
+
+    /// Convert the colours of the cells to the nearest ones the terminal can
+    /// display.
+    #[allow(unused)]
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    fn render(&mut self, change: Changeset) -> Result<(), RendererErr> {
         match change {
             Changeset::Add { x, y, cell } => self.update(x, y, cell),
             Changeset::Remove { x, y } => self.remove(x, y),
@@ -32,49 +156,965 @@ impl<T: Write> Terminal<T> {
         }
     }
 
-    fn remove(&mut self, x: u16, y: u16) {
-        self.apply(&empty_at(x, y))
+    fn remove(&mut self, x: u16, y: u16) -> Result<(), RendererErr> {
+        self.update(x, y, Cell::Empty)
     }
 
-    fn update(&mut self, x: u16, y: u16, cell: Cell) {
+    fn update(&mut self, x: u16, y: u16, cell: Cell) -> Result<(), RendererErr> {
         use Command::*;
 
-        match cell {
-            Cell::Empty => self.apply(&empty_at(x, y)),
+        let (pen, grapheme) = match cell {
+            Cell::Empty => (Pen::reset(), Grapheme::from(' ')),
             Cell::Filled {
-                character,
+                grapheme,
                 foreground,
                 background,
-            } => self.apply(&[
-                MoveTo(x, y),
-                ApplyStyle(foreground),
-                ApplyStyle(background),
-                Write(character.to_string()),
-            ]),
+                attributes,
+            } => (
+                Pen {
+                    foreground: foreground.downsample(self.color_depth),
+                    background: background.downsample(self.color_depth),
+                    attributes: match self.styled_underlines {
+                        true => attributes.downsample(self.color_depth),
+                        false => attributes.plain_underline(),
+                    },
+                },
+                grapheme,
+            ),
+            // Drawn with the wide grapheme on its left.
+            Cell::Continuation => return Ok(()),
+        };
+
+        self.move_to(x, y)?;
+        self.set_pen(pen)?;
+
+        // A zero-width grapheme doesn't move the cursor, it's drawn over a
+        // space to fill its cell.
+        let text = match grapheme.width() {
+            0 => format!(" {}", grapheme),
+            _ => grapheme.to_string(),
         };
+        self.apply(&[Write(text)])?;
+
+        // Writing in the last column leaves the cursor in the pending wrap
+        // state, where relative moves aren't reliable.
+        let next = x + grapheme.width().max(1) as u16;
+        self.cursor = match next < self.width {
+            true => Some((next, y)),
+            false => None,
+        };
+
+        Ok(())
+    }
+
+    fn set_pen(&mut self, pen: Pen) -> Result<(), RendererErr> {
+        let styles = transition(self.pen.as_ref(), &pen);
+
+        if !styles.is_empty() {
+            self.apply(&[Command::ApplyStyles(styles)])?;
+        }
+
+        self.pen = Some(pen);
+
+        Ok(())
+    }
+
+    fn move_to(&mut self, x: u16, y: u16) -> Result<(), RendererErr> {
+        if self.cursor == Some((x, y)) {
+            return Ok(());
+        }
+
+        let ops = cheapest_move(&self.escapes, self.cursor, x, y);
+        self.apply(&ops)?;
+        self.cursor = Some((x, y));
+
+        Ok(())
     }
 
-    fn apply(&mut self, ops: &[Command]) {
+    fn apply(&mut self, ops: &[Command]) -> Result<(), RendererErr> {
         for op in ops {
-            write!(self.out, "{}", op).unwrap()
+            self.escapes.write(&mut self.buf, op)?;
         }
+
+        Ok(())
+    }
+
+    /// Write the assembled frame with a single write and flush it.
+    fn flush(&mut self) -> Result<(), RendererErr> {
+        if !self.buf.is_empty() {
+            self.out.write_all(&self.buf)?;
+        }
+
+        self.out.flush()?;
+
+        Ok(())
+    }
+
+    fn draw(&mut self, changesets: Changesets) -> Result<(), RendererErr> {
+        if self.synchronized {
+            self.apply(&[Command::BeginSynchronizedUpdate])?;
+        }
+        let start = self.buf.len();
+
+        if self.invalidated {
+            self.apply(&[Command::ApplyStyle(Style::Reset), Command::Clear])?;
+            self.pen = Some(Pen::reset());
+            self.invalidated = false;
+        }
+
+        for change in changesets {
+            self.render(change)?;
+        }
+
+        if self.synchronized {
+            match self.buf.len() == start {
+                true => self.buf.clear(),
+                false => self.apply(&[Command::EndSynchronizedUpdate])?,
+            }
+        }
+
+        self.flush()
     }
 }
 
-fn empty_at(x: u16, y: u16) -> [Command; 3] {
+/// Shortest sequence of commands moving the cursor from `from` to (x, y).
+fn cheapest_move(escapes: &Escapes, from: Option<(u16, u16)>, x: u16, y: u16) -> Vec<Command> {
     use Command::*;
 
-    [
-        MoveTo(x, y),
-        ApplyStyle(Style::Reset),
-        Write(String::from("\0")),
-    ]
+    let absolute = vec![MoveTo(x, y)];
+    let Some((cx, cy)) = from else {
+        return absolute;
+    };
+
+    let vertical = match y.cmp(&cy) {
+        std::cmp::Ordering::Less => Some(MoveUp(cy - y)),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(MoveDown(y - cy)),
+    };
+
+    let horizontal = match x.cmp(&cx) {
+        std::cmp::Ordering::Less => vec![MoveLeft(cx - x)],
+        std::cmp::Ordering::Equal => vec![],
+        std::cmp::Ordering::Greater => vec![MoveRight(x - cx)],
+    };
+    let from_start = match x {
+        0 => vec![CarriageReturn],
+        x => vec![CarriageReturn, MoveRight(x)],
+    };
+
+    vec![horizontal, from_start]
+        .into_iter()
+        .map(|mut ops| {
+            ops.extend(vertical.clone());
+            ops
+        })
+        .chain(Some(absolute))
+        .min_by_key(|ops| escapes.encoded_len(ops))
+        .expect("at least one candidate")
+}
+
+/// Styles to apply to go from the `from` pen to the `to` pen, the terminal
+/// is reset first when the current pen is unknown or when a style has to go
+/// back to its default.
+fn transition(from: Option<&Pen>, to: &Pen) -> Vec<Style> {
+    let reset = match from {
+        None => true,
+        Some(from) => {
+            (to.foreground == Style::Reset && from.foreground != Style::Reset)
+                || (to.background == Style::Reset && from.background != Style::Reset)
+        }
+    };
+
+    let mut styles = Vec::new();
+    let from = match (reset, from) {
+        (false, Some(from)) => from.clone(),
+        _ => {
+            styles.push(Style::Reset);
+            Pen::reset()
+        }
+    };
+
+    if to.foreground != from.foreground {
+        styles.push(to.foreground.clone());
+    }
+    if to.background != from.background {
+        styles.push(to.background.clone());
+    }
+
+    // Bold and dim are turned off by the same parameter, the one that is
+    // kept has to be turned on again.
+    let mut attributes = from.attributes;
+    for attribute in from.attributes.iter() {
+        if to.attributes.contains(attribute) || !attributes.contains(attribute) {
+            continue;
+        }
+
+        styles.push(Style::NoAttribute(attribute));
+        attributes = match attribute {
+            Attribute::Bold | Attribute::Dim => {
+                attributes.without(Attribute::Bold).without(Attribute::Dim)
+            }
+            _ => attributes.without(attribute),
+        };
+    }
+    for attribute in to.attributes.iter() {
+        if attribute != Attribute::Underline && !attributes.contains(attribute) {
+            styles.push(Style::Attribute(attribute));
+        }
+    }
+
+    // Turning the underline on and changing its style use the same
+    // parameter.
+    if let Some(style) = to.attributes.underline_style() {
+        if attributes.underline_style() != Some(style) {
+            styles.push(Style::Underline(style));
+        }
+    }
+
+    // Turning the underline off keeps its colour on the terminal.
+    let color = to.attributes.underline_color();
+    if color != from.attributes.underline_color() {
+        styles.push(match color {
+            Some(color) => Style::UnderlineColor(color),
+            None => Style::NoUnderlineColor,
+        });
+    }
+
+    styles
 }
 
 impl<T: Write> Renderer for Terminal<T> {
-    fn submit(&mut self, changesets: Changesets) {
-        for change in changesets {
-            self.render(change)
+    fn submit(&mut self, changesets: Changesets) -> Result<(), RendererErr> {
+        let result = self.draw(changesets);
+        self.buf.clear();
+
+        // Part of the frame may have been written, the screen, the cursor
+        // and the styles can't be trusted anymore.
+        if result.is_err() {
+            self.invalidate();
+            self.pen = None;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::framebuffer::CellStyle;
+    use crate::terminal::{
+        bg, fg,
+        terminfo::{cap, test_util::compile, Terminfo},
+        Color,
+        Color::*,
+        UnderlineStyle,
+    };
+
+    fn add(x: u16, y: u16, cell: Cell) -> Changeset {
+        Changeset::Add { x, y, cell }
+    }
+
+    fn colors(foreground: Color, background: Color) -> CellStyle {
+        CellStyle {
+            foreground: fg(foreground),
+            background: bg(background),
+            attributes: Attributes::empty(),
+        }
+    }
+
+    /// Style of the cells of most tests, with `attributes`.
+    fn attributes(attributes: Attributes) -> CellStyle {
+        CellStyle {
+            attributes,
+            ..colors(Red, Yellow)
+        }
+    }
+
+    fn styles(styles: Vec<Style>) -> Command {
+        Command::ApplyStyles(styles)
+    }
+
+    /// Styles applied to the first glyph written by a new renderer.
+    fn pen() -> Command {
+        styles(vec![Style::Reset, fg(Red), bg(Yellow)])
+    }
+
+    fn submit(width: u16, changesets: Changesets) -> String {
+        let mut out = Vec::new();
+        Terminal::new(&mut out, width).submit(changesets).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn adjacent_cells_write_through() {
+        let out = submit(
+            10,
+            vec![
+                add(2, 1, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(3, 1, Cell::styled('b'.into(), &colors(Red, Yellow))),
+                add(4, 1, Cell::styled('c'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(out, format!("{}{}abc", Command::MoveTo(2, 1), pen()));
+    }
+
+    #[test]
+    fn relative_moves() {
+        let out = submit(
+            80,
+            vec![
+                add(2, 1, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(5, 1, Cell::styled('b'.into(), &colors(Red, Yellow))),
+                add(1, 1, Cell::styled('c'.into(), &colors(Red, Yellow))),
+                add(0, 2, Cell::styled('d'.into(), &colors(Red, Yellow))),
+                add(1, 0, Cell::styled('e'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b{}c{}{}d{}e",
+                Command::MoveTo(2, 1),
+                pen(),
+                Command::MoveRight(2),
+                Command::MoveLeft(5),
+                Command::CarriageReturn,
+                Command::MoveDown(1),
+                Command::MoveUp(2),
+            )
+        );
+    }
+
+    #[test]
+    fn absolute_move_when_cheaper() {
+        let out = submit(
+            200,
+            vec![
+                add(150, 100, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(2, 3, Cell::styled('b'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b",
+                Command::MoveTo(150, 100),
+                pen(),
+                Command::MoveTo(2, 3),
+            )
+        );
+    }
+
+    #[test]
+    fn absolute_move_after_last_column() {
+        let out = submit(
+            3,
+            vec![
+                add(2, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(0, 1, Cell::styled('b'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b",
+                Command::MoveTo(2, 0),
+                pen(),
+                Command::MoveTo(0, 1),
+            )
+        );
+    }
+
+    #[test]
+    fn remove_writes_a_blank() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                Changeset::Remove { x: 1, y: 0 },
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{} ",
+                Command::MoveTo(0, 0),
+                pen(),
+                styles(vec![Style::Reset])
+            )
+        );
+    }
+
+    #[test]
+    fn only_changed_styles_are_applied() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(1, 0, Cell::styled('b'.into(), &colors(Green, Yellow))),
+                add(2, 0, Cell::styled('c'.into(), &colors(Green, Blue))),
+                add(3, 0, Cell::styled('d'.into(), &colors(Red, Black))),
+                add(4, 0, Cell::styled('e'.into(), &colors(Red, Black))),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b{}c{}de",
+                Command::MoveTo(0, 0),
+                pen(),
+                styles(vec![fg(Green)]),
+                styles(vec![bg(Blue)]),
+                styles(vec![fg(Red), bg(Black)]),
+            )
+        );
+    }
+
+    #[test]
+    fn only_changed_attributes_are_applied() {
+        let out = submit(
+            10,
+            vec![
+                add(
+                    0,
+                    0,
+                    Cell::styled('a'.into(), &attributes(Attribute::Bold | Attribute::Italic)),
+                ),
+                add(
+                    1,
+                    0,
+                    Cell::styled(
+                        'b'.into(),
+                        &attributes(Attribute::Bold | Attribute::Underline),
+                    ),
+                ),
+                add(
+                    2,
+                    0,
+                    Cell::styled(
+                        'c'.into(),
+                        &attributes(Attribute::Dim | Attribute::Underline),
+                    ),
+                ),
+                add(
+                    3,
+                    0,
+                    Cell::styled('d'.into(), &attributes(Attributes::from(Attribute::Dim))),
+                ),
+                add(
+                    4,
+                    0,
+                    Cell::styled('e'.into(), &attributes(Attributes::empty())),
+                ),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b{}c{}d{}e",
+                Command::MoveTo(0, 0),
+                styles(vec![
+                    Style::Reset,
+                    fg(Red),
+                    bg(Yellow),
+                    Style::Attribute(Attribute::Bold),
+                    Style::Attribute(Attribute::Italic),
+                ]),
+                styles(vec![
+                    Style::NoAttribute(Attribute::Italic),
+                    Style::Attribute(Attribute::Underline),
+                ]),
+                styles(vec![
+                    Style::NoAttribute(Attribute::Bold),
+                    Style::Attribute(Attribute::Dim),
+                ]),
+                styles(vec![Style::NoAttribute(Attribute::Underline)]),
+                styles(vec![Style::NoAttribute(Attribute::Dim)]),
+            )
+        );
+    }
+
+    #[test]
+    fn underline_styles_and_colors() {
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+        renderer.set_styled_underlines(true);
+
+        let curly = Attributes::empty().with_underline(UnderlineStyle::Curly);
+        renderer
+            .submit(vec![
+                add(
+                    0,
+                    0,
+                    Cell::styled('a'.into(), &attributes(curly.with_underline_color(Blue))),
+                ),
+                add(1, 0, Cell::styled('b'.into(), &attributes(curly))),
+                add(
+                    2,
+                    0,
+                    Cell::styled(
+                        'c'.into(),
+                        &attributes(Attributes::from(Attribute::Underline)),
+                    ),
+                ),
+                add(
+                    3,
+                    0,
+                    Cell::styled('d'.into(), &attributes(Attributes::empty())),
+                ),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}a{}b{}c{}d",
+                Command::MoveTo(0, 0),
+                styles(vec![
+                    Style::Reset,
+                    fg(Red),
+                    bg(Yellow),
+                    Style::Underline(UnderlineStyle::Curly),
+                    Style::UnderlineColor(Blue),
+                ]),
+                styles(vec![Style::NoUnderlineColor]),
+                styles(vec![Style::Underline(UnderlineStyle::Single)]),
+                styles(vec![Style::NoAttribute(Attribute::Underline)]),
+            )
+        );
+    }
+
+    #[test]
+    fn plain_underline_fallback() {
+        let out = submit(
+            10,
+            vec![
+                add(
+                    0,
+                    0,
+                    Cell::styled(
+                        'a'.into(),
+                        &attributes(
+                            Attributes::empty()
+                                .with_underline(UnderlineStyle::Dotted)
+                                .with_underline_color(Blue),
+                        ),
+                    ),
+                ),
+                add(
+                    1,
+                    0,
+                    Cell::styled(
+                        'b'.into(),
+                        &attributes(Attributes::from(Attribute::Underline)),
+                    ),
+                ),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}ab",
+                Command::MoveTo(0, 0),
+                styles(vec![
+                    Style::Reset,
+                    fg(Red),
+                    bg(Yellow),
+                    Style::Underline(UnderlineStyle::Single),
+                ]),
+            )
+        );
+    }
+
+    #[test]
+    fn empty_cell_resets_attributes() {
+        let out = submit(
+            10,
+            vec![
+                add(
+                    0,
+                    0,
+                    Cell::styled(
+                        'a'.into(),
+                        &attributes(Attributes::from(Attribute::Reverse)),
+                    ),
+                ),
+                Changeset::Remove { x: 1, y: 0 },
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{} ",
+                Command::MoveTo(0, 0),
+                styles(vec![
+                    Style::Reset,
+                    fg(Red),
+                    bg(Yellow),
+                    Style::Attribute(Attribute::Reverse),
+                ]),
+                styles(vec![Style::Reset]),
+            )
+        );
+    }
+
+    #[test]
+    fn default_colors_dont_reset() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(
+                    1,
+                    0,
+                    Cell::styled('b'.into(), &colors(Default, Indexed(236))),
+                ),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}a{}b",
+                Command::MoveTo(0, 0),
+                pen(),
+                styles(vec![fg(Default), bg(Indexed(236))]),
+            )
+        );
+    }
+
+    #[test]
+    fn colors_are_downsampled() {
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+        renderer.set_color_depth(ColorDepth::Indexed256);
+
+        renderer
+            .submit(vec![
+                add(
+                    0,
+                    0,
+                    Cell::styled(
+                        'a'.into(),
+                        &colors(Color::Rgb { r: 255, g: 0, b: 0 }, Yellow),
+                    ),
+                ),
+                add(
+                    1,
+                    0,
+                    Cell::styled(
+                        'b'.into(),
+                        &colors(Color::Rgb { r: 250, g: 2, b: 3 }, Yellow),
+                    ),
+                ),
+            ])
+            .unwrap();
+        renderer.set_color_depth(ColorDepth::Monochrome);
+        renderer
+            .submit(vec![add(
+                2,
+                0,
+                Cell::styled('c'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}ab{}c",
+                Command::MoveTo(0, 0),
+                styles(vec![Style::Reset, fg(Indexed(196)), bg(Yellow)]),
+                styles(vec![fg(Default), bg(Default)]),
+            )
+        );
+    }
+
+    #[test]
+    fn styles_are_kept_between_frames() {
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+
+        renderer
+            .submit(vec![add(
+                0,
+                0,
+                Cell::styled('a'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+        renderer
+            .submit(vec![add(
+                5,
+                0,
+                Cell::styled('b'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}a{}b",
+                Command::MoveTo(0, 0),
+                pen(),
+                Command::MoveRight(4)
+            )
+        );
+    }
+
+    /// Writer recording each call to `write` and `flush`.
+    #[derive(Default)]
+    struct Calls {
+        writes: Vec<Vec<u8>>,
+        flushes: usize,
+    }
+
+    impl Write for Calls {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn one_write_and_flush_per_frame() {
+        let mut calls = Calls::default();
+        let mut renderer = Terminal::new(&mut calls, 10);
+
+        renderer
+            .submit(vec![
+                add(0, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(4, 2, Cell::styled('b'.into(), &colors(Red, Yellow))),
+            ])
+            .unwrap();
+        renderer
+            .submit(vec![add(
+                1,
+                1,
+                Cell::styled('c'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+        renderer.submit(vec![]).unwrap();
+
+        assert_eq!(calls.writes.len(), 2);
+        assert_eq!(calls.flushes, 3);
+        assert_eq!(
+            String::from_utf8(calls.writes[1].clone()).unwrap(),
+            format!("{}c", Command::MoveTo(1, 1))
+        );
+    }
+
+    #[test]
+    fn synchronized_output() {
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+        renderer.set_synchronized_output(true);
+
+        renderer
+            .submit(vec![add(
+                0,
+                0,
+                Cell::styled('a'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+        renderer.submit(vec![]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}{}a{}",
+                Command::BeginSynchronizedUpdate,
+                Command::MoveTo(0, 0),
+                pen(),
+                Command::EndSynchronizedUpdate
+            )
+        );
+    }
+
+    /// Writer failing the first write.
+    #[derive(Default)]
+    struct Broken {
+        failed: bool,
+        out: Vec<u8>,
+    }
+
+    impl Write for Broken {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+                return Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+            }
+
+            self.out.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
         }
     }
+
+    #[test]
+    fn failed_write_invalidates() {
+        let mut broken = Broken::default();
+        let mut renderer = Terminal::new(&mut broken, 10);
+
+        let result = renderer.submit(vec![add(
+            0,
+            0,
+            Cell::styled('a'.into(), &colors(Red, Yellow)),
+        )]);
+        assert!(matches!(result, Err(RendererErr::Writing(_))));
+        assert!(renderer.is_invalidated());
+
+        renderer
+            .submit(vec![add(
+                0,
+                0,
+                Cell::styled('a'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+        assert!(!renderer.is_invalidated());
+
+        assert_eq!(
+            String::from_utf8(broken.out).unwrap(),
+            format!(
+                "{}{}{}{}a",
+                Style::Reset,
+                Command::Clear,
+                Command::MoveTo(0, 0),
+                styles(vec![fg(Red), bg(Yellow)])
+            )
+        );
+    }
+
+    #[test]
+    fn invalidate_forgets_cursor() {
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+
+        renderer
+            .submit(vec![add(
+                0,
+                0,
+                Cell::styled('a'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+        renderer.invalidate();
+        renderer
+            .submit(vec![add(
+                1,
+                0,
+                Cell::styled('b'.into(), &colors(Red, Yellow)),
+            )])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}{}a{}{}{}{}b",
+                Command::MoveTo(0, 0),
+                pen(),
+                Style::Reset,
+                Command::Clear,
+                Command::MoveTo(1, 0),
+                styles(vec![fg(Red), bg(Yellow)]),
+            )
+        );
+    }
+
+    #[test]
+    fn terminfo_escapes() {
+        let mut strings = vec![None; cap::SET_A_BACKGROUND + 1];
+        strings[cap::CURSOR_ADDRESS] = Some(&b"\x1B[%i%p1%d;%p2%df"[..]);
+        strings[cap::SET_A_FOREGROUND] = Some(&b"<fg %p1%d>"[..]);
+        strings[cap::SET_A_BACKGROUND] = Some(&b"<bg %p1%d>"[..]);
+        let terminfo = Terminfo::parse(&compile("test", &[], &[], &strings)).unwrap();
+
+        let mut out = Vec::new();
+        let mut renderer = Terminal::new(&mut out, 10);
+        renderer.set_escapes(Escapes::new(Some(terminfo)));
+        renderer
+            .submit(vec![
+                add(2, 1, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(7, 1, Cell::styled('b'.into(), &colors(Red, Yellow))),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "\x1B[2;3f{}<fg 1><bg 3>a{}b",
+                Style::Reset,
+                Command::MoveRight(4)
+            )
+        );
+    }
+
+    #[test]
+    fn wide_graphemes_move_the_cursor_by_two() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled("中".into(), &colors(Red, Yellow))),
+                add(2, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+                add(8, 0, Cell::styled("文".into(), &colors(Red, Yellow))),
+                add(0, 1, Cell::styled('b'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(
+            out,
+            format!(
+                "{}{}中a{}文{}b",
+                Command::MoveTo(0, 0),
+                pen(),
+                Command::MoveRight(5),
+                Command::MoveTo(0, 1),
+            )
+        );
+    }
+
+    #[test]
+    fn zero_width_graphemes_are_drawn_over_a_space() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled("\u{301}".into(), &colors(Red, Yellow))),
+                add(1, 0, Cell::styled('a'.into(), &colors(Red, Yellow))),
+            ],
+        );
+
+        assert_eq!(out, format!("{}{} \u{301}a", Command::MoveTo(0, 0), pen()));
+    }
+
+    #[test]
+    fn continuations_are_skipped() {
+        let out = submit(
+            10,
+            vec![
+                add(0, 0, Cell::styled("中".into(), &colors(Red, Yellow))),
+                Changeset::Update {
+                    x: 1,
+                    y: 0,
+                    cell: Cell::Continuation,
+                },
+            ],
+        );
+
+        assert_eq!(out, format!("{}{}中", Command::MoveTo(0, 0), pen()));
+    }
 }
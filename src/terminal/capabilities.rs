@@ -0,0 +1,222 @@
+use super::{input::Key, terminfo::Terminfo, ColorDepth};
+
+/// What the terminal supports, as advertised by the environment and its
+/// terminfo entry.
+#[derive(Debug, Clone, Default)]
+#[allow(unused)]
+pub struct Capabilities {
+    pub color_depth: ColorDepth,
+    /// The terminal supports underline styles and colours.
+    pub styled_underlines: bool,
+    pub terminfo: Option<Terminfo>,
+}
+
+#[allow(unused)]
+impl Capabilities {
+    /// Detect the capabilities from the process environment and the entry
+    /// of `$TERM`.
+    pub fn detect() -> Self {
+        let terminfo = Terminfo::from_env().ok();
+
+        Self::from_env(|name| std::env::var(name).ok(), terminfo)
+    }
+
+    /// Detect the capabilities from the variables returned by `var` and an
+    /// already loaded entry.
+    ///
+    /// `NO_COLOR` disables the colours unless `CLICOLOR_FORCE` is set, in
+    /// which case at least the 16 named colours are used, even on a dumb
+    /// terminal. Otherwise the deepest of `COLORTERM`, the name of the
+    /// terminal and the `colors` capability wins, the `Tc` and `RGB`
+    /// extended capabilities advertise 24-bit colours.
+    pub fn from_env<F>(var: F, terminfo: Option<Terminfo>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let set = |name| var(name).filter(|v| !v.is_empty());
+
+        let forced = set("CLICOLOR_FORCE").is_some_and(|v| v != "0");
+        let term = set("TERM").unwrap_or_default();
+
+        let advertised = [
+            set("COLORTERM").and_then(|v| from_colorterm(&v)),
+            from_term(&term),
+            terminfo.as_ref().and_then(|t| t.colors()).map(from_colors),
+            terminfo
+                .as_ref()
+                .filter(|t| t.extended_flag("Tc") || t.extended_flag("RGB"))
+                .map(|_| ColorDepth::TrueColor),
+        ]
+        .iter()
+        .flatten()
+        .max()
+        .copied();
+
+        let color_depth = if forced {
+            advertised.map_or(ColorDepth::Ansi16, |d| d.max(ColorDepth::Ansi16))
+        } else if set("NO_COLOR").is_some() || term.is_empty() || term == "dumb" {
+            ColorDepth::Monochrome
+        } else {
+            // Without any hint assume the common denominator.
+            advertised.unwrap_or(ColorDepth::Ansi16)
+        };
+
+        let styled_underlines = terminfo
+            .as_ref()
+            .is_some_and(|t| t.extended_string("Smulx").is_some());
+
+        Self {
+            color_depth,
+            styled_underlines,
+            terminfo,
+        }
+    }
+
+    /// Key sequences of the terminfo entry, see `Input::add_key`.
+    pub fn keys(&self) -> Vec<(Vec<u8>, Key)> {
+        self.terminfo
+            .as_ref()
+            .map(Terminfo::keys)
+            .unwrap_or_default()
+    }
+}
+
+fn from_colorterm(value: &str) -> Option<ColorDepth> {
+    match value {
+        "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+        _ => None,
+    }
+}
+
+fn from_term(term: &str) -> Option<ColorDepth> {
+    if ["direct", "truecolor", "24bit"]
+        .iter()
+        .any(|s| term.contains(s))
+    {
+        Some(ColorDepth::TrueColor)
+    } else if term.contains("256color") {
+        Some(ColorDepth::Indexed256)
+    } else {
+        None
+    }
+}
+
+fn from_colors(colors: i32) -> ColorDepth {
+    match colors {
+        n if n >= 0x1000000 => ColorDepth::TrueColor,
+        n if n >= 256 => ColorDepth::Indexed256,
+        n if n >= 8 => ColorDepth::Ansi16,
+        _ => ColorDepth::Monochrome,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terminal::terminfo::{
+        cap,
+        test_util::{compile, extend},
+    };
+
+    fn detect(vars: &[(&str, &str)], colors: Option<i16>) -> ColorDepth {
+        let terminfo = colors.map(|n| {
+            let mut numbers = vec![-1; cap::MAX_COLORS + 1];
+            numbers[cap::MAX_COLORS] = n;
+            Terminfo::parse(&compile("test", &[], &numbers, &[])).unwrap()
+        });
+
+        let var = |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| String::from(*v))
+        };
+
+        Capabilities::from_env(var, terminfo).color_depth
+    }
+
+    #[test]
+    fn colorterm() {
+        let depth = detect(&[("TERM", "xterm"), ("COLORTERM", "truecolor")], Some(8));
+        assert_eq!(depth, ColorDepth::TrueColor);
+
+        let depth = detect(&[("TERM", "xterm"), ("COLORTERM", "24bit")], None);
+        assert_eq!(depth, ColorDepth::TrueColor);
+
+        let depth = detect(&[("TERM", "xterm"), ("COLORTERM", "yes")], Some(8));
+        assert_eq!(depth, ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn term_name() {
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")], None),
+            ColorDepth::Indexed256
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-direct")], None),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "unknown")], None), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")], None), ColorDepth::Monochrome);
+        assert_eq!(detect(&[], None), ColorDepth::Monochrome);
+    }
+
+    #[test]
+    fn terminfo_colors() {
+        assert_eq!(detect(&[("TERM", "t")], Some(256)), ColorDepth::Indexed256);
+        assert_eq!(detect(&[("TERM", "t")], Some(8)), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "t")], Some(0)), ColorDepth::Monochrome);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")], Some(8)),
+            ColorDepth::Indexed256
+        );
+    }
+
+    #[test]
+    fn no_color() {
+        let vars = [("TERM", "xterm-256color"), ("NO_COLOR", "1")];
+        assert_eq!(detect(&vars, Some(256)), ColorDepth::Monochrome);
+
+        let vars = [("TERM", "xterm-256color"), ("NO_COLOR", "")];
+        assert_eq!(detect(&vars, Some(256)), ColorDepth::Indexed256);
+    }
+
+    #[test]
+    fn clicolor_force() {
+        let vars = [
+            ("TERM", "xterm-256color"),
+            ("NO_COLOR", "1"),
+            ("CLICOLOR_FORCE", "1"),
+        ];
+        assert_eq!(detect(&vars, None), ColorDepth::Indexed256);
+
+        let vars = [("TERM", "dumb"), ("CLICOLOR_FORCE", "1")];
+        assert_eq!(detect(&vars, None), ColorDepth::Ansi16);
+
+        let vars = [("TERM", "dumb"), ("CLICOLOR_FORCE", "0")];
+        assert_eq!(detect(&vars, None), ColorDepth::Monochrome);
+    }
+
+    #[test]
+    fn extended_capabilities() {
+        let var = |name: &str| match name {
+            "TERM" => Some(String::from("tmux-256color")),
+            _ => None,
+        };
+        let entry = |booleans: &[(&str, bool)], strings: &[(&str, &[u8])]| {
+            let bytes = extend(compile("test", &[], &[], &[]), booleans, strings);
+            Some(Terminfo::parse(&bytes).unwrap())
+        };
+
+        let capabilities = Capabilities::from_env(var, entry(&[("Tc", true)], &[]));
+        assert_eq!(capabilities.color_depth, ColorDepth::TrueColor);
+        assert!(!capabilities.styled_underlines);
+
+        let capabilities = Capabilities::from_env(var, entry(&[("RGB", true)], &[]));
+        assert_eq!(capabilities.color_depth, ColorDepth::TrueColor);
+
+        let capabilities = Capabilities::from_env(var, entry(&[], &[("Smulx", b"\x1B[4:%p1%dm")]));
+        assert_eq!(capabilities.color_depth, ColorDepth::Indexed256);
+        assert!(capabilities.styled_underlines);
+    }
+}
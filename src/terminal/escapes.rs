@@ -0,0 +1,270 @@
+use std::io::Write;
+
+use super::{
+    terminfo::{cap, Param, Terminfo},
+    Color, Command, Style, UnderlineStyle, Visibility,
+};
+
+/// Encode the commands with the sequences of a terminfo entry.
+///
+/// Only the cursor address, the indexed colours, a lone reset, the cursor
+/// visibility, the clear and the alternate screen are taken from the entry,
+/// along with the underline styles when it has `Smulx`. Everything else, and whatever the
+/// entry doesn't define, is written with the built-in xterm sequences of
+/// `Command`.
+#[derive(Debug, Clone, Default)]
+pub struct Escapes {
+    terminfo: Option<Terminfo>,
+}
+
+#[allow(unused)]
+impl Escapes {
+    pub fn new(terminfo: Option<Terminfo>) -> Self {
+        Self { terminfo }
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, command: &Command) -> std::io::Result<()> {
+        let Some(info) = self.terminfo.as_ref() else {
+            return write!(out, "{}", command);
+        };
+
+        let string = |index| info.string(index).map(<[u8]>::to_vec);
+        let encoded = match command {
+            Command::MoveTo(x, y) => info.expand(
+                cap::CURSOR_ADDRESS,
+                &[Param::Number(*y as i32), Param::Number(*x as i32)],
+            ),
+            Command::Cursor(Visibility::Hidden) => string(cap::CURSOR_INVISIBLE),
+            Command::Cursor(Visibility::Show) => string(cap::CURSOR_NORMAL),
+            Command::Clear => string(cap::CLEAR_SCREEN),
+            Command::ApplyStyle(Style::Reset) => string(cap::EXIT_ATTRIBUTE_MODE),
+            Command::EnterAlternateScreen => string(cap::ENTER_CA_MODE),
+            Command::LeaveAlternateScreen => string(cap::EXIT_CA_MODE),
+            Command::ApplyStyle(style) => return self.write_styles(out, info, &[style.clone()]),
+            Command::ApplyStyles(styles) => return self.write_styles(out, info, styles),
+            _ => None,
+        };
+
+        match encoded {
+            Some(bytes) => out.write_all(&bytes),
+            None => write!(out, "{}", command),
+        }
+    }
+
+    /// Length of the encoded commands, to compare equivalent sequences.
+    pub fn encoded_len(&self, commands: &[Command]) -> usize {
+        let mut buf = Vec::new();
+        for command in commands {
+            let _ = self.write(&mut buf, command);
+        }
+
+        buf.len()
+    }
+
+    /// Write the styles the entry can encode on their own, the others are
+    /// grouped in a single SGR written first so a reset still comes before
+    /// the colours.
+    fn write_styles<W: Write>(
+        &self,
+        out: &mut W,
+        info: &Terminfo,
+        styles: &[Style],
+    ) -> std::io::Result<()> {
+        let mut sgr = Vec::new();
+        let mut encoded = Vec::new();
+
+        for style in styles {
+            match encode_style(info, style) {
+                Some(bytes) => encoded.extend(bytes),
+                None => sgr.push(style.clone()),
+            }
+        }
+
+        if !sgr.is_empty() {
+            write!(out, "{}", Command::ApplyStyles(sgr))?;
+        }
+
+        out.write_all(&encoded)
+    }
+}
+
+fn encode_style(info: &Terminfo, style: &Style) -> Option<Vec<u8>> {
+    match style {
+        Style::Foreground(color) => encode_color(info, cap::SET_A_FOREGROUND, color),
+        Style::Background(color) => encode_color(info, cap::SET_A_BACKGROUND, color),
+        Style::Underline(style) => {
+            let n = match style {
+                UnderlineStyle::Single => 1,
+                UnderlineStyle::Double => 2,
+                UnderlineStyle::Curly => 3,
+                UnderlineStyle::Dotted => 4,
+                UnderlineStyle::Dashed => 5,
+            };
+
+            info.expand_extended("Smulx", &[Param::Number(n)])
+        }
+        _ => None,
+    }
+}
+
+/// `setaf` and `setab` only take palette indexes, RGB and the default
+/// colours go through the built-in sequences.
+fn encode_color(info: &Terminfo, index: usize, color: &Color) -> Option<Vec<u8>> {
+    let n = color.index()?;
+
+    // An 8 colours entry can't encode the bright colours.
+    if info.colors().is_some_and(|colors| n as i32 >= colors) {
+        return None;
+    }
+
+    info.expand(index, &[Param::Number(n as i32)])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terminal::{
+        bg, fg,
+        terminfo::test_util::{compile, extend},
+        Attribute,
+    };
+
+    const SETAF: &[u8] = b"\x1B[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m";
+    const SETAB: &[u8] = b"\x1B[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m";
+
+    fn entry(colors: i16, strings: &[(usize, &[u8])]) -> Terminfo {
+        let mut numbers = vec![-1; cap::MAX_COLORS + 1];
+        numbers[cap::MAX_COLORS] = colors;
+
+        let mut table = vec![None; cap::SET_A_BACKGROUND + 1];
+        for (index, string) in strings {
+            table[*index] = Some(*string);
+        }
+
+        Terminfo::parse(&compile("test", &[], &numbers, &table)).unwrap()
+    }
+
+    fn encode(escapes: &Escapes, command: Command) -> String {
+        let mut out = Vec::new();
+        escapes.write(&mut out, &command).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn without_terminfo() {
+        let escapes = Escapes::default();
+
+        for command in [
+            Command::MoveTo(3, 4),
+            Command::Clear,
+            Command::ApplyStyles(vec![Style::Reset, fg(Color::Red)]),
+        ] {
+            assert_eq!(encode(&escapes, command.clone()), command.to_string());
+        }
+    }
+
+    #[test]
+    fn terminfo_sequences() {
+        let escapes = Escapes::new(Some(entry(
+            256,
+            &[
+                (cap::CURSOR_ADDRESS, b"\x1B[%i%p1%02d;%p2%02dH"),
+                (cap::CURSOR_INVISIBLE, b"<civis>"),
+                (cap::CURSOR_NORMAL, b"<cnorm>"),
+                (cap::CLEAR_SCREEN, b"<clear>"),
+                (cap::ENTER_CA_MODE, b"<smcup>"),
+                (cap::EXIT_CA_MODE, b"<rmcup>"),
+            ],
+        )));
+
+        assert_eq!(encode(&escapes, Command::MoveTo(3, 4)), "\x1B[05;04H");
+        assert_eq!(
+            encode(&escapes, Command::Cursor(Visibility::Hidden)),
+            "<civis>"
+        );
+        assert_eq!(
+            encode(&escapes, Command::Cursor(Visibility::Show)),
+            "<cnorm>"
+        );
+        assert_eq!(encode(&escapes, Command::Clear), "<clear>");
+        assert_eq!(encode(&escapes, Command::EnterAlternateScreen), "<smcup>");
+        assert_eq!(encode(&escapes, Command::LeaveAlternateScreen), "<rmcup>");
+        assert_eq!(
+            encode(&escapes, Command::EnableBracketedPaste),
+            Command::EnableBracketedPaste.to_string()
+        );
+        assert_eq!(escapes.encoded_len(&[Command::MoveTo(0, 0)]), 8);
+    }
+
+    #[test]
+    fn missing_capabilities_fall_back() {
+        let escapes = Escapes::new(Some(entry(256, &[])));
+
+        for command in [
+            Command::MoveTo(3, 4),
+            Command::Cursor(Visibility::Hidden),
+            Command::EnterAlternateScreen,
+            Command::ApplyStyle(fg(Color::Red)),
+        ] {
+            assert_eq!(encode(&escapes, command.clone()), command.to_string());
+        }
+    }
+
+    #[test]
+    fn indexed_colors() {
+        let escapes = Escapes::new(Some(entry(
+            256,
+            &[
+                (cap::SET_A_FOREGROUND, SETAF),
+                (cap::SET_A_BACKGROUND, SETAB),
+            ],
+        )));
+
+        let styles = vec![
+            Style::Reset,
+            fg(Color::BrightRed),
+            bg(Color::Indexed(200)),
+            Style::Attribute(Attribute::Bold),
+        ];
+        assert_eq!(
+            encode(&escapes, Command::ApplyStyles(styles)),
+            "\x1B[0;1m\x1B[91m\x1B[48;5;200m"
+        );
+
+        let styles = vec![fg(Color::Rgb { r: 1, g: 2, b: 3 }), bg(Color::Default)];
+        assert_eq!(
+            encode(&escapes, Command::ApplyStyles(styles)),
+            "\x1B[38;2;1;2;3;49m"
+        );
+
+        assert_eq!(
+            encode(&escapes, Command::ApplyStyle(bg(Color::Blue))),
+            "\x1B[44m"
+        );
+    }
+
+    #[test]
+    fn colors_beyond_the_entry() {
+        let escapes = Escapes::new(Some(entry(8, &[(cap::SET_A_FOREGROUND, b"\x1B[3%p1%dm")])));
+
+        assert_eq!(
+            encode(&escapes, Command::ApplyStyle(fg(Color::Green))),
+            "\x1B[32m"
+        );
+        assert_eq!(
+            encode(&escapes, Command::ApplyStyle(fg(Color::BrightGreen))),
+            "\x1B[92m"
+        );
+    }
+
+    #[test]
+    fn extended_underline_styles() {
+        let bytes = compile("test", &[], &[], &[]);
+        let bytes = extend(bytes, &[], &[("Smulx", b"\x1B[4:%p1%dm")]);
+        let escapes = Escapes::new(Some(Terminfo::parse(&bytes).unwrap()));
+
+        let styles = vec![Style::Underline(UnderlineStyle::Curly)];
+        assert_eq!(encode(&escapes, Command::ApplyStyles(styles)), "\x1B[4:3m");
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Error, ErrorKind, Read, Write},
+    os::fd::AsRawFd,
+    time::{Duration, Instant},
+};
+
+use super::{Command, TTY};
+
+pub mod parser;
+
+pub use self::parser::Parser;
+
+/// How long to wait for the rest of a sequence after a lone ESC before
+/// reporting it as the Escape key.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    F(u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+impl Modifiers {
+    /// Decode the modifier parameter used by xterm in `CSI 1;<m>A`, the
+    /// value is 1 plus a bitmask of shift (1), alt (2), ctrl (4) and meta
+    /// (8), meta is reported as alt.
+    pub fn from_xterm(m: u16) -> Self {
+        let bits = m.saturating_sub(1);
+
+        Self {
+            shift: bits & 1 != 0,
+            alt: bits & (2 | 8) != 0,
+            ctrl: bits & 4 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self { key, modifiers }
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        Self::new(key, Modifiers::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum MouseKind {
+    Press(MouseButton),
+    Release(MouseButton),
+    Drag(MouseButton),
+    Move,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// Mouse event, `x` and `y` are 0-based cell coordinates matching the
+/// `Framebuffer` ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    pub kind: MouseKind,
+    pub x: u16,
+    pub y: u16,
+    pub modifiers: Modifiers,
+}
+
+/// State of a mode reported by the terminal in reply to DECRQM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeStatus {
+    NotRecognized,
+    Set,
+    Reset,
+    PermanentlySet,
+    PermanentlyReset,
+}
+
+impl ModeStatus {
+    pub fn from_param(param: u16) -> Self {
+        match param {
+            1 => ModeStatus::Set,
+            2 => ModeStatus::Reset,
+            3 => ModeStatus::PermanentlySet,
+            4 => ModeStatus::PermanentlyReset,
+            _ => ModeStatus::NotRecognized,
+        }
+    }
+
+    /// True if the application can toggle the mode.
+    pub fn is_supported(&self) -> bool {
+        matches!(
+            self,
+            ModeStatus::Set | ModeStatus::Reset | ModeStatus::PermanentlySet
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(unused)]
+pub enum Event {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// Text received between the bracketed paste markers.
+    Paste(String),
+    /// Reply to a DECRQM mode request.
+    Mode {
+        mode: u16,
+        status: ModeStatus,
+    },
+    /// Reply to a primary device attributes request, every terminal answers
+    /// it so it marks the end of the replies to the previous requests.
+    PrimaryAttributes,
+}
+
+/// Read and decode events from the tty.
+pub struct Input {
+    tty: File,
+    parser: Parser,
+    escape_timeout: Duration,
+    /// Events received while waiting for the reply to a query.
+    queue: VecDeque<Event>,
+}
+
+#[allow(unused)]
+impl Input {
+    pub fn new() -> Result<Self, Error> {
+        let tty = std::fs::OpenOptions::new().read(true).open(TTY)?;
+
+        Ok(Self {
+            tty,
+            parser: Parser::new(),
+            escape_timeout: ESCAPE_TIMEOUT,
+            queue: VecDeque::new(),
+        })
+    }
+
+    /// Decode `sequence` as `key`, see `Parser::add_key`.
+    pub fn add_key(&mut self, sequence: Vec<u8>, key: Key) {
+        self.parser.add_key(sequence, key);
+    }
+
+    pub fn set_escape_timeout(&mut self, timeout: Duration) {
+        self.escape_timeout = timeout;
+    }
+
+    /// Wait up to `timeout` for the next event, `None` blocks until one is
+    /// available. Returns `Ok(None)` when the timeout expires or when the
+    /// wait is interrupted by a signal, like SIGWINCH.
+    pub fn read(&mut self, timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        if let Some(event) = self.queue.pop_front() {
+            return Ok(Some(event));
+        }
+
+        self.read_tty(timeout)
+    }
+
+    /// Ask the terminal for the state of a DEC private mode, the request is
+    /// followed by a primary device attributes request so terminals that
+    /// ignore DECRQM are detected without waiting for the whole `timeout`.
+    ///
+    /// Events received in the meantime are kept for the next calls to
+    /// `read`.
+    pub fn query_mode<W: Write>(
+        &mut self,
+        out: &mut W,
+        mode: u16,
+        timeout: Duration,
+    ) -> Result<ModeStatus, Error> {
+        write!(
+            out,
+            "{}{}",
+            Command::RequestMode(mode),
+            Command::RequestPrimaryAttributes
+        )?;
+        out.flush()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut status = ModeStatus::NotRecognized;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+
+            match self.read_tty(Some(remaining))? {
+                Some(Event::Mode { mode: m, status: s }) if m == mode => status = s,
+                Some(Event::PrimaryAttributes) => break,
+                Some(event) => self.queue.push_back(event),
+                None => {}
+            }
+        }
+
+        Ok(status)
+    }
+
+    fn read_tty(&mut self, timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        loop {
+            if let Some(event) = self.parser.next() {
+                return Ok(Some(event));
+            }
+
+            if self.parser.is_pending() {
+                if !self.wait_escape()? {
+                    return Ok(self.parser.flush());
+                }
+            } else if !self.wait(timeout)? {
+                return Ok(None);
+            }
+
+            let mut buf = [0; 1024];
+            let n = match self.tty.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => return Ok(None),
+                Err(e) => return Err(e),
+            };
+
+            if n == 0 {
+                return Err(Error::from(ErrorKind::UnexpectedEof));
+            }
+
+            self.parser.advance(&buf[..n]);
+        }
+    }
+
+    /// Poll the tty, returns false on timeout or interruption.
+    fn wait(&self, timeout: Option<Duration>) -> Result<bool, Error> {
+        match self.poll(timeout) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => Ok(false),
+            result => result,
+        }
+    }
+
+    /// Poll the tty for the rest of a pending sequence, returns false on
+    /// timeout. An interruption doesn't cut the escape timeout short, the
+    /// sequence would be read as separate keys.
+    fn wait_escape(&self) -> Result<bool, Error> {
+        let deadline = Instant::now() + self.escape_timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.poll(Some(remaining)) {
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// Poll the tty, returns false on timeout.
+    fn poll(&self, timeout: Option<Duration>) -> Result<bool, Error> {
+        let mut fds = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.map_or(-1, |t| t.as_millis().min(i32::MAX as u128) as i32);
+
+        unsafe {
+            if libc::poll(&mut fds, 1, timeout) < 0 {
+                return Err(Error::last_os_error());
+            }
+        }
+
+        Ok(fds.revents != 0)
+    }
+}
@@ -0,0 +1,854 @@
+use super::{Event, Key, KeyEvent, ModeStatus, Modifiers, MouseButton, MouseEvent, MouseKind};
+
+const ESC: u8 = 0x1B;
+const PASTE_START: &[u8] = b"\x1B[200~";
+const PASTE_END: &[u8] = b"\x1B[201~";
+
+/// Outcome of decoding the front of the buffer.
+#[derive(Debug, PartialEq)]
+enum Parsed {
+    Event(Event, usize),
+    PasteStart(usize),
+    Skip(usize),
+    Incomplete,
+}
+
+/// Decode the bytes read from the tty into events.
+///
+/// Bytes are accumulated with `advance`, sequences split across reads are
+/// kept until they are complete. A lone ESC is ambiguous with the start of a
+/// sequence and stays pending until `flush` is called, usually after a short
+/// timeout without more input.
+///
+/// Bracketed pastes are collected until the end marker is received, however
+/// many reads it takes, and reported as a single `Event::Paste`.
+///
+/// Sequences added with `add_key`, usually learnt from terminfo, are matched
+/// before the built-in decoding, unless it reads a longer sequence.
+#[derive(Debug, Default)]
+pub struct Parser {
+    buf: Vec<u8>,
+    paste: Option<Vec<u8>>,
+    /// Extra key sequences, longest first.
+    keys: Vec<(Vec<u8>, Key)>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Decode `sequence` as `key`, empty sequences are ignored.
+    pub fn add_key(&mut self, sequence: Vec<u8>, key: Key) {
+        if sequence.is_empty() {
+            return;
+        }
+
+        self.keys.retain(|(s, _)| *s != sequence);
+        let at = self
+            .keys
+            .partition_point(|(s, _)| s.len() >= sequence.len());
+        self.keys.insert(at, (sequence, key));
+    }
+
+    /// True if bytes are waiting for the rest of a sequence, a paste in
+    /// progress isn't pending, its end can arrive at any time.
+    pub fn is_pending(&self) -> bool {
+        self.paste.is_none() && !self.buf.is_empty()
+    }
+
+    /// Next complete event, `None` if more bytes are needed.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Event> {
+        self.consume(false)
+    }
+
+    /// Next event assuming no more bytes will follow, resolving a pending
+    /// ESC as the Escape key or as an Alt prefix.
+    pub fn flush(&mut self) -> Option<Event> {
+        self.consume(true)
+    }
+
+    fn consume(&mut self, eof: bool) -> Option<Event> {
+        while !self.buf.is_empty() {
+            if let Some(paste) = self.paste.as_mut() {
+                let Some(end) = find(&self.buf, PASTE_END) else {
+                    // Keep what could be the start of the end marker.
+                    let n = self.buf.len() - partial_suffix(&self.buf, PASTE_END);
+                    paste.extend(self.buf.drain(..n));
+                    return None;
+                };
+
+                paste.extend(self.buf.drain(..end));
+                self.buf.drain(..PASTE_END.len());
+
+                let text = String::from_utf8_lossy(&paste[..]).into_owned();
+                self.paste = None;
+
+                return Some(Event::Paste(text));
+            }
+
+            let parsed = match self.parse_key(eof) {
+                Some(parsed) => parsed,
+                None => parse(&self.buf, eof),
+            };
+
+            match parsed {
+                Parsed::Event(event, n) => {
+                    self.buf.drain(..n);
+                    return Some(event);
+                }
+                Parsed::PasteStart(n) => {
+                    self.buf.drain(..n);
+                    self.paste = Some(Vec::new());
+                }
+                Parsed::Skip(n) => {
+                    self.buf.drain(..n);
+                }
+                Parsed::Incomplete => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Match the front of the buffer against the extra key sequences, the
+    /// longest one wins so a sequence that is the prefix of another one
+    /// waits for more bytes, like a lone ESC. A sequence that is the prefix
+    /// of a longer built-in one, like a modified arrow, doesn't match it.
+    fn parse_key(&self, eof: bool) -> Option<Parsed> {
+        let partial = self
+            .keys
+            .iter()
+            .any(|(s, _)| s.len() > self.buf.len() && s.starts_with(&self.buf));
+
+        if partial && !eof {
+            return Some(Parsed::Incomplete);
+        }
+
+        let (sequence, k) = self.keys.iter().find(|(s, _)| self.buf.starts_with(s))?;
+
+        match parse(&self.buf, eof) {
+            Parsed::Incomplete => Some(Parsed::Incomplete),
+            Parsed::Event(_, n) | Parsed::PasteStart(n) | Parsed::Skip(n) if n > sequence.len() => {
+                None
+            }
+            _ => Some(Parsed::Event(key(*k, Modifiers::default()), sequence.len())),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Length of the longest suffix of `buf` that is a proper prefix of `marker`.
+fn partial_suffix(buf: &[u8], marker: &[u8]) -> usize {
+    (1..marker.len())
+        .rev()
+        .find(|&n| buf.ends_with(&marker[..n]))
+        .unwrap_or(0)
+}
+
+fn key(key: Key, modifiers: Modifiers) -> Event {
+    Event::Key(KeyEvent::new(key, modifiers))
+}
+
+fn ctrl() -> Modifiers {
+    Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    }
+}
+
+fn parse(buf: &[u8], eof: bool) -> Parsed {
+    let none = Modifiers::default();
+
+    match buf[0] {
+        ESC => parse_escape(buf, eof),
+        b'\r' => Parsed::Event(key(Key::Enter, none), 1),
+        b'\t' => Parsed::Event(key(Key::Tab, none), 1),
+        0x7F | 0x08 => Parsed::Event(key(Key::Backspace, none), 1),
+        0x00 => Parsed::Event(key(Key::Char(' '), ctrl()), 1),
+        b @ 0x01..=0x1A => Parsed::Event(key(Key::Char((b'a' + b - 1) as char), ctrl()), 1),
+        b @ 0x1C..=0x1F => Parsed::Event(key(Key::Char((b + 0x40) as char), ctrl()), 1),
+        b @ 0x20..=0x7E => Parsed::Event(key(Key::Char(b as char), none), 1),
+        _ => parse_utf8(buf, eof),
+    }
+}
+
+fn parse_utf8(buf: &[u8], eof: bool) -> Parsed {
+    let width = match buf[0] {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return Parsed::Skip(1),
+    };
+
+    if buf.len() < width {
+        return match eof {
+            true => Parsed::Skip(buf.len()),
+            false => Parsed::Incomplete,
+        };
+    }
+
+    match std::str::from_utf8(&buf[..width])
+        .ok()
+        .and_then(|s| s.chars().next())
+    {
+        Some(c) => Parsed::Event(key(Key::Char(c), Modifiers::default()), width),
+        None => Parsed::Skip(1),
+    }
+}
+
+fn parse_escape(buf: &[u8], eof: bool) -> Parsed {
+    if buf.len() == 1 {
+        return match eof {
+            true => Parsed::Event(key(Key::Escape, Modifiers::default()), 1),
+            false => Parsed::Incomplete,
+        };
+    }
+
+    let parsed = match buf[1] {
+        b'[' => parse_csi(buf),
+        b'O' => parse_ss3(buf),
+        _ => return with_alt(parse(&buf[1..], eof)),
+    };
+
+    match parsed {
+        // `ESC [` and `ESC O` with nothing after them are Alt+[ and Alt+O.
+        Parsed::Incomplete if eof => with_alt(parse(&buf[1..], eof)),
+        parsed => parsed,
+    }
+}
+
+fn with_alt(parsed: Parsed) -> Parsed {
+    match parsed {
+        Parsed::Event(Event::Key(mut event), n) => {
+            event.modifiers.alt = true;
+            Parsed::Event(Event::Key(event), n + 1)
+        }
+        Parsed::Event(event, n) => Parsed::Event(event, n + 1),
+        Parsed::PasteStart(n) => Parsed::PasteStart(n + 1),
+        Parsed::Skip(n) => Parsed::Skip(n + 1),
+        Parsed::Incomplete => Parsed::Incomplete,
+    }
+}
+
+/// `ESC O <final>`, sent for arrows, Home/End and F1-F4 in application
+/// cursor mode.
+fn parse_ss3(buf: &[u8]) -> Parsed {
+    let Some(&b) = buf.get(2) else {
+        return Parsed::Incomplete;
+    };
+
+    match final_key(b) {
+        Some(k) => Parsed::Event(key(k, Modifiers::default()), 3),
+        None => Parsed::Skip(3),
+    }
+}
+
+/// `ESC [ <params> <intermediates> <final>`.
+fn parse_csi(buf: &[u8]) -> Parsed {
+    // The linux console sends `ESC [ [ A` to `ESC [ [ E` for F1-F5.
+    if buf.get(2) == Some(&b'[') {
+        return match buf.get(3) {
+            None => Parsed::Incomplete,
+            Some(&b @ b'A'..=b'E') => {
+                Parsed::Event(key(Key::F(b - b'A' + 1), Modifiers::default()), 4)
+            }
+            Some(_) => Parsed::Skip(4),
+        };
+    }
+
+    for (i, &b) in buf.iter().enumerate().skip(2) {
+        match b {
+            0x20..=0x3F => continue,
+            0x40..=0x7E if buf[..=i] == *PASTE_START => return Parsed::PasteStart(i + 1),
+            0x40..=0x7E => {
+                return match csi(&buf[2..i], b) {
+                    Some(event) => Parsed::Event(event, i + 1),
+                    None => Parsed::Skip(i + 1),
+                }
+            }
+            _ => return Parsed::Skip(i),
+        }
+    }
+
+    Parsed::Incomplete
+}
+
+fn csi(params: &[u8], final_byte: u8) -> Option<Event> {
+    if let Some(params) = params.strip_prefix(b"<") {
+        return sgr_mouse(params, final_byte);
+    }
+
+    if let Some(params) = params.strip_prefix(b"?") {
+        return private_reply(params, final_byte);
+    }
+
+    let params = parse_params(params)?;
+    let param = |i: usize| params.get(i).copied().flatten();
+    let modifiers = Modifiers::from_xterm(param(1).unwrap_or(1));
+
+    match final_byte {
+        b'~' => {
+            let k = match param(0)? {
+                1 | 7 => Key::Home,
+                2 => Key::Insert,
+                3 => Key::Delete,
+                4 | 8 => Key::End,
+                5 => Key::PageUp,
+                6 => Key::PageDown,
+                n @ 11..=15 => Key::F((n - 10) as u8),
+                n @ 17..=21 => Key::F((n - 11) as u8),
+                n @ 23..=24 => Key::F((n - 12) as u8),
+                _ => return None,
+            };
+
+            Some(key(k, modifiers))
+        }
+        b'Z' => Some(key(Key::BackTab, Modifiers::default())),
+        b'u' => {
+            let k = match param(0)? {
+                9 => Key::Tab,
+                13 => Key::Enter,
+                27 => Key::Escape,
+                127 => Key::Backspace,
+                n => Key::Char(char::from_u32(n.into())?),
+            };
+
+            Some(key(k, modifiers))
+        }
+        b => final_key(b).map(|k| key(k, modifiers)),
+    }
+}
+
+/// SGR mouse report, `CSI < b ; x ; y M` on press and motion and
+/// `CSI < b ; x ; y m` on release, with 1-based coordinates.
+fn sgr_mouse(params: &[u8], final_byte: u8) -> Option<Event> {
+    let params = parse_params(params)?;
+    let (b, x, y) = match params[..] {
+        [Some(b), Some(x), Some(y)] => (b, x, y),
+        _ => return None,
+    };
+
+    let modifiers = Modifiers {
+        shift: b & 4 != 0,
+        alt: b & 8 != 0,
+        ctrl: b & 16 != 0,
+    };
+    let button = match b & 3 {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    };
+
+    let kind = if b & 64 != 0 {
+        match b & 3 {
+            0 => MouseKind::ScrollUp,
+            1 => MouseKind::ScrollDown,
+            2 => MouseKind::ScrollLeft,
+            _ => MouseKind::ScrollRight,
+        }
+    } else if b & 32 != 0 {
+        button.map_or(MouseKind::Move, MouseKind::Drag)
+    } else if final_byte == b'm' {
+        MouseKind::Release(button?)
+    } else if final_byte == b'M' {
+        MouseKind::Press(button?)
+    } else {
+        return None;
+    };
+
+    Some(Event::Mouse(MouseEvent {
+        kind,
+        x: x.saturating_sub(1),
+        y: y.saturating_sub(1),
+        modifiers,
+    }))
+}
+
+/// Replies to DECRQM, `CSI ? <mode> ; <status> $ y`, and to the primary
+/// device attributes request, `CSI ? <attributes> c`.
+fn private_reply(params: &[u8], final_byte: u8) -> Option<Event> {
+    match final_byte {
+        b'c' => Some(Event::PrimaryAttributes),
+        b'y' => match parse_params(params.strip_suffix(b"$")?)?[..] {
+            [Some(mode), Some(status)] => Some(Event::Mode {
+                mode,
+                status: ModeStatus::from_param(status),
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Keys identified by the final byte of a CSI or SS3 sequence.
+fn final_key(b: u8) -> Option<Key> {
+    match b {
+        b'A' => Some(Key::Up),
+        b'B' => Some(Key::Down),
+        b'C' => Some(Key::Right),
+        b'D' => Some(Key::Left),
+        b'H' => Some(Key::Home),
+        b'F' => Some(Key::End),
+        b'P' => Some(Key::F(1)),
+        b'Q' => Some(Key::F(2)),
+        b'R' => Some(Key::F(3)),
+        b'S' => Some(Key::F(4)),
+        _ => None,
+    }
+}
+
+/// Split `;` separated numeric parameters, empty parameters are `None`.
+fn parse_params(params: &[u8]) -> Option<Vec<Option<u16>>> {
+    if params.is_empty() {
+        return Some(Vec::new());
+    }
+
+    params
+        .split(|&b| b == b';')
+        .map(|p| match p {
+            [] => Some(None),
+            p => std::str::from_utf8(p).ok()?.parse().ok().map(Some),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn events(bytes: &[u8]) -> Vec<Event> {
+        let mut parser = Parser::new();
+        parser.advance(bytes);
+
+        let mut events = Vec::new();
+        while let Some(event) = parser.flush() {
+            events.push(event);
+        }
+
+        events
+    }
+
+    fn plain(k: Key) -> Event {
+        key(k, Modifiers::default())
+    }
+
+    fn alt(k: Key) -> Event {
+        key(
+            k,
+            Modifiers {
+                alt: true,
+                ..Modifiers::default()
+            },
+        )
+    }
+
+    #[test]
+    fn printable() {
+        assert_eq!(
+            events(b"aZ~"),
+            vec![
+                plain(Key::Char('a')),
+                plain(Key::Char('Z')),
+                plain(Key::Char('~'))
+            ]
+        );
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(
+            events("é€😀".as_bytes()),
+            vec![
+                plain(Key::Char('é')),
+                plain(Key::Char('€')),
+                plain(Key::Char('😀'))
+            ]
+        );
+    }
+
+    #[test]
+    fn utf8_split_across_reads() {
+        let bytes = "€".as_bytes();
+        let mut parser = Parser::new();
+
+        parser.advance(&bytes[..1]);
+        assert_eq!(parser.next(), None);
+        parser.advance(&bytes[1..]);
+        assert_eq!(parser.next(), Some(plain(Key::Char('€'))));
+    }
+
+    #[test]
+    fn invalid_utf8_is_skipped() {
+        assert_eq!(events(b"\xFFa"), vec![plain(Key::Char('a'))]);
+    }
+
+    #[test]
+    fn control_keys() {
+        assert_eq!(
+            events(b"\r\t\x7F\x01\x1A\x00\x1F"),
+            vec![
+                plain(Key::Enter),
+                plain(Key::Tab),
+                plain(Key::Backspace),
+                key(Key::Char('a'), ctrl()),
+                key(Key::Char('z'), ctrl()),
+                key(Key::Char(' '), ctrl()),
+                key(Key::Char('_'), ctrl()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lone_escape_waits_for_timeout() {
+        let mut parser = Parser::new();
+        parser.advance(b"\x1B");
+
+        assert_eq!(parser.next(), None);
+        assert!(parser.is_pending());
+        assert_eq!(parser.flush(), Some(plain(Key::Escape)));
+        assert!(!parser.is_pending());
+    }
+
+    #[test]
+    fn escape_split_across_reads() {
+        let mut parser = Parser::new();
+
+        parser.advance(b"\x1B");
+        assert_eq!(parser.next(), None);
+        parser.advance(b"[");
+        assert_eq!(parser.next(), None);
+        parser.advance(b"A");
+        assert_eq!(parser.next(), Some(plain(Key::Up)));
+    }
+
+    #[test]
+    fn alt_prefix() {
+        assert_eq!(events(b"\x1Bx"), vec![alt(Key::Char('x'))]);
+        assert_eq!(events(b"\x1B\x1B"), vec![alt(Key::Escape)]);
+        assert_eq!(events(b"\x1B["), vec![alt(Key::Char('['))]);
+        assert_eq!(events(b"\x1B\x1B[A"), vec![alt(Key::Up)]);
+    }
+
+    #[test]
+    fn arrows_csi_and_ss3() {
+        assert_eq!(
+            events(b"\x1B[A\x1B[B\x1B[C\x1B[D\x1BOA\x1BOB\x1BOC\x1BOD"),
+            vec![
+                plain(Key::Up),
+                plain(Key::Down),
+                plain(Key::Right),
+                plain(Key::Left),
+                plain(Key::Up),
+                plain(Key::Down),
+                plain(Key::Right),
+                plain(Key::Left),
+            ]
+        );
+    }
+
+    #[test]
+    fn home_end_variants() {
+        assert_eq!(
+            events(b"\x1B[H\x1B[F\x1BOH\x1BOF\x1B[1~\x1B[4~\x1B[7~\x1B[8~"),
+            vec![
+                plain(Key::Home),
+                plain(Key::End),
+                plain(Key::Home),
+                plain(Key::End),
+                plain(Key::Home),
+                plain(Key::End),
+                plain(Key::Home),
+                plain(Key::End),
+            ]
+        );
+    }
+
+    #[test]
+    fn tilde_keys() {
+        assert_eq!(
+            events(b"\x1B[2~\x1B[3~\x1B[5~\x1B[6~"),
+            vec![
+                plain(Key::Insert),
+                plain(Key::Delete),
+                plain(Key::PageUp),
+                plain(Key::PageDown),
+            ]
+        );
+    }
+
+    #[test]
+    fn function_keys() {
+        let expected = (1..=12).map(|n| plain(Key::F(n))).collect::<Vec<_>>();
+
+        assert_eq!(
+            events(
+                b"\x1BOP\x1BOQ\x1BOR\x1BOS\x1B[15~\x1B[17~\x1B[18~\x1B[19~\x1B[20~\x1B[21~\x1B[23~\x1B[24~"
+            ),
+            expected
+        );
+        assert_eq!(
+            events(b"\x1B[11~\x1B[12~\x1B[13~\x1B[14~\x1B[[A\x1B[[E"),
+            vec![
+                plain(Key::F(1)),
+                plain(Key::F(2)),
+                plain(Key::F(3)),
+                plain(Key::F(4)),
+                plain(Key::F(1)),
+                plain(Key::F(5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn modified_keys() {
+        assert_eq!(
+            events(b"\x1B[1;5C\x1B[1;2A\x1B[3;3~\x1B[1;6P\x1B[Z"),
+            vec![
+                key(Key::Right, ctrl()),
+                key(
+                    Key::Up,
+                    Modifiers {
+                        shift: true,
+                        ..Modifiers::default()
+                    }
+                ),
+                alt(Key::Delete),
+                key(
+                    Key::F(1),
+                    Modifiers {
+                        shift: true,
+                        ctrl: true,
+                        ..Modifiers::default()
+                    }
+                ),
+                plain(Key::BackTab),
+            ]
+        );
+    }
+
+    #[test]
+    fn csi_u() {
+        assert_eq!(
+            events(b"\x1B[97;5u\x1B[13u"),
+            vec![key(Key::Char('a'), ctrl()), plain(Key::Enter)]
+        );
+    }
+
+    fn mouse(kind: MouseKind, x: u16, y: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            x,
+            y,
+            modifiers: Modifiers::default(),
+        })
+    }
+
+    #[test]
+    fn mouse_click() {
+        assert_eq!(
+            events(b"\x1B[<0;1;1M\x1B[<0;1;1m\x1B[<2;80;24M\x1B[<1;3;4m"),
+            vec![
+                mouse(MouseKind::Press(MouseButton::Left), 0, 0),
+                mouse(MouseKind::Release(MouseButton::Left), 0, 0),
+                mouse(MouseKind::Press(MouseButton::Right), 79, 23),
+                mouse(MouseKind::Release(MouseButton::Middle), 2, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn mouse_drag_and_move() {
+        assert_eq!(
+            events(b"\x1B[<32;10;5M\x1B[<35;11;5M"),
+            vec![
+                mouse(MouseKind::Drag(MouseButton::Left), 9, 4),
+                mouse(MouseKind::Move, 10, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn mouse_wheel() {
+        assert_eq!(
+            events(b"\x1B[<64;5;5M\x1B[<65;5;5M\x1B[<66;5;5M\x1B[<67;5;5M"),
+            vec![
+                mouse(MouseKind::ScrollUp, 4, 4),
+                mouse(MouseKind::ScrollDown, 4, 4),
+                mouse(MouseKind::ScrollLeft, 4, 4),
+                mouse(MouseKind::ScrollRight, 4, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn mouse_modifiers() {
+        assert_eq!(
+            events(b"\x1B[<20;300;2M"),
+            vec![Event::Mouse(MouseEvent {
+                kind: MouseKind::Press(MouseButton::Left),
+                x: 299,
+                y: 1,
+                modifiers: Modifiers {
+                    shift: true,
+                    alt: false,
+                    ctrl: true,
+                },
+            })]
+        );
+    }
+
+    #[test]
+    fn mouse_split_across_reads() {
+        let mut parser = Parser::new();
+
+        parser.advance(b"\x1B[<0;12");
+        assert_eq!(parser.next(), None);
+        parser.advance(b";7M");
+        assert_eq!(
+            parser.next(),
+            Some(mouse(MouseKind::Press(MouseButton::Left), 11, 6))
+        );
+    }
+
+    #[test]
+    fn paste() {
+        assert_eq!(
+            events(b"a\x1B[200~hello\r\n\x1B[Aworld\x1B[201~b"),
+            vec![
+                plain(Key::Char('a')),
+                Event::Paste(String::from("hello\r\n\x1B[Aworld")),
+                plain(Key::Char('b')),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_paste() {
+        assert_eq!(
+            events(b"\x1B[200~\x1B[201~"),
+            vec![Event::Paste(String::new())]
+        );
+    }
+
+    #[test]
+    fn paste_split_across_reads() {
+        let mut parser = Parser::new();
+
+        parser.advance(b"\x1B[20");
+        assert_eq!(parser.next(), None);
+        parser.advance(b"0~first ");
+        assert_eq!(parser.next(), None);
+        assert!(!parser.is_pending());
+        parser.advance("second é".as_bytes());
+        assert_eq!(parser.next(), None);
+        parser.advance(b"\x1B[20");
+        assert_eq!(parser.flush(), None);
+        parser.advance(b"1~x");
+        assert_eq!(
+            parser.next(),
+            Some(Event::Paste(String::from("first second é")))
+        );
+        assert_eq!(parser.next(), Some(plain(Key::Char('x'))));
+    }
+
+    #[test]
+    fn paste_utf8_split_across_reads() {
+        let bytes = "€".as_bytes();
+        let mut parser = Parser::new();
+
+        parser.advance(b"\x1B[200~");
+        parser.advance(&bytes[..2]);
+        assert_eq!(parser.next(), None);
+        parser.advance(&bytes[2..]);
+        parser.advance(b"\x1B[201~");
+        assert_eq!(parser.next(), Some(Event::Paste(String::from("€"))));
+    }
+
+    #[test]
+    fn unknown_sequences_are_dropped() {
+        assert_eq!(
+            events(b"\x1B[99~\x1B[?1;2xq\x1B[?2026$y"),
+            vec![plain(Key::Char('q'))]
+        );
+    }
+
+    #[test]
+    fn mode_report() {
+        assert_eq!(
+            events(b"\x1B[?2026;2$y\x1B[?2004;0$y\x1B[?1049;4$y"),
+            vec![
+                Event::Mode {
+                    mode: 2026,
+                    status: ModeStatus::Reset
+                },
+                Event::Mode {
+                    mode: 2004,
+                    status: ModeStatus::NotRecognized
+                },
+                Event::Mode {
+                    mode: 1049,
+                    status: ModeStatus::PermanentlyReset
+                },
+            ]
+        );
+        assert!(ModeStatus::Reset.is_supported());
+        assert!(!ModeStatus::PermanentlyReset.is_supported());
+    }
+
+    #[test]
+    fn primary_attributes() {
+        assert_eq!(
+            events(b"\x1B[?62;22c\x1B[?1;2c"),
+            vec![Event::PrimaryAttributes, Event::PrimaryAttributes]
+        );
+    }
+
+    #[test]
+    fn added_keys() {
+        let mut parser = Parser::new();
+        parser.add_key(b"\x1B[11~".to_vec(), Key::F(1));
+        parser.add_key(b"\x1B[1".to_vec(), Key::Home);
+        parser.add_key(Vec::new(), Key::End);
+
+        parser.advance(b"\x1B[1");
+        assert_eq!(parser.next(), None);
+
+        parser.advance(b"1~a\x1B[1");
+        assert_eq!(parser.next(), Some(plain(Key::F(1))));
+        assert_eq!(parser.next(), Some(plain(Key::Char('a'))));
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.flush(), Some(plain(Key::Home)));
+    }
+
+    #[test]
+    fn added_keys_dont_cut_longer_sequences() {
+        let mut parser = Parser::new();
+        parser.add_key(b"\x1B[1".to_vec(), Key::Home);
+        parser.advance(b"\x1B[1;5C\x1B[1");
+
+        assert_eq!(parser.next(), Some(key(Key::Right, ctrl())));
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.flush(), Some(plain(Key::Home)));
+    }
+
+    #[test]
+    fn added_keys_replace_sequences() {
+        let mut parser = Parser::new();
+        parser.add_key(b"\x1BOA".to_vec(), Key::Down);
+        parser.add_key(b"\x1BOA".to_vec(), Key::Up);
+        parser.advance(b"\x1BOA\x1BOB");
+
+        assert_eq!(parser.next(), Some(plain(Key::Up)));
+        assert_eq!(parser.next(), Some(plain(Key::Down)));
+    }
+}
@@ -1,38 +1,251 @@
-use std::{
-    borrow::Cow,
-    io::{Error, Write},
-    os::fd::IntoRawFd,
-};
+use std::{borrow::Cow, io::Error, os::fd::IntoRawFd};
 
 use libc::{ioctl, TIOCGWINSZ};
 
+mod capabilities;
+mod escapes;
+pub mod input;
+mod palette;
+mod raw;
+mod resize;
+mod session;
+pub mod terminfo;
+
+pub use self::capabilities::Capabilities;
+pub use self::escapes::Escapes;
+pub use self::palette::ColorDepth;
+pub use self::raw::RawMode;
+pub use self::resize::ResizeNotifier;
+pub use self::session::Session;
+
 static TTY: &str = "/dev/tty";
 
+/// DEC private mode of the synchronized output (2026).
+pub const SYNCHRONIZED_OUTPUT: u16 = 2026;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Style {
     Foreground(Color),
     Background(Color),
+    Attribute(Attribute),
+    /// Turn off an attribute, turning off `Bold` or `Dim` turns off both.
+    NoAttribute(Attribute),
+    /// Turn the underline on with a style.
+    Underline(UnderlineStyle),
+    UnderlineColor(Color),
+    /// Draw the underline with the foreground colour.
+    NoUnderlineColor,
     Reset,
 }
 
+impl Style {
+    /// SGR parameters of the style, without the CSI introducer and the final
+    /// `m`.
+    pub fn params(&self) -> Cow<str> {
+        use Style::*;
+
+        match self {
+            Foreground(color) => color.fg(),
+            Background(color) => color.bg(),
+            Attribute(attribute) => Cow::Borrowed(attribute.on()),
+            NoAttribute(attribute) => Cow::Borrowed(attribute.off()),
+            Underline(style) => Cow::Borrowed(style.on()),
+            UnderlineColor(color) => color.underline(),
+            NoUnderlineColor => Cow::Borrowed("59"),
+            Reset => Cow::Borrowed("0"),
+        }
+    }
+}
+
 impl std::fmt::Display for Style {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Style::*;
+        write!(f, "\x1B[{}m", self.params())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(unused)]
+pub enum Attribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Reverse,
+    Strikethrough,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 6] = [
+        Attribute::Bold,
+        Attribute::Dim,
+        Attribute::Italic,
+        Attribute::Underline,
+        Attribute::Reverse,
+        Attribute::Strikethrough,
+    ];
 
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// SGR parameter turning the attribute on.
+    pub fn on(&self) -> &'static str {
         match self {
-            Foreground(color) => match color {
-                Color::Rgb { .. } => write!(f, "\x1B[38;2{}m", color.fg()),
-                _ => write!(f, "\x1B[{}m", color.fg()),
-            },
-            Background(color) => match color {
-                Color::Rgb { .. } => write!(f, "\x1B[48;2{}m", color.fg()),
-                _ => write!(f, "\x1B[{}m", color.bg()),
-            },
-            Reset => write!(f, "\x1B[0m"),
+            Attribute::Bold => "1",
+            Attribute::Dim => "2",
+            Attribute::Italic => "3",
+            Attribute::Underline => "4",
+            Attribute::Reverse => "7",
+            Attribute::Strikethrough => "9",
+        }
+    }
+
+    /// SGR parameter turning the attribute off.
+    pub fn off(&self) -> &'static str {
+        match self {
+            Attribute::Bold | Attribute::Dim => "22",
+            Attribute::Italic => "23",
+            Attribute::Underline => "24",
+            Attribute::Reverse => "27",
+            Attribute::Strikethrough => "29",
+        }
+    }
+}
+
+/// Shape of the underline, terminals that don't support the styles draw a
+/// single underline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(unused)]
+pub enum UnderlineStyle {
+    #[default]
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// SGR parameter turning the underline on with this style, using the
+    /// `4:<style>` sub-parameter form.
+    pub fn on(&self) -> &'static str {
+        match self {
+            UnderlineStyle::Single => "4",
+            UnderlineStyle::Double => "4:2",
+            UnderlineStyle::Curly => "4:3",
+            UnderlineStyle::Dotted => "4:4",
+            UnderlineStyle::Dashed => "4:5",
         }
     }
 }
 
+/// Set of `Attribute`, built with `|`, with the style and the colour of the
+/// underline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Attributes {
+    set: u8,
+    underline: UnderlineStyle,
+    underline_color: Option<Color>,
+}
+
+#[allow(unused)]
+impl Attributes {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set == 0
+    }
+
+    pub fn contains(&self, attribute: Attribute) -> bool {
+        self.set & attribute.bit() != 0
+    }
+
+    pub fn with(mut self, attribute: Attribute) -> Self {
+        self.set |= attribute.bit();
+        self
+    }
+
+    /// Remove an attribute, removing `Underline` also drops its style and
+    /// colour.
+    pub fn without(mut self, attribute: Attribute) -> Self {
+        self.set &= !attribute.bit();
+
+        if attribute == Attribute::Underline {
+            self.underline = UnderlineStyle::default();
+            self.underline_color = None;
+        }
+
+        self
+    }
+
+    /// Underline the text with the given style.
+    pub fn with_underline(self, style: UnderlineStyle) -> Self {
+        let mut attributes = self.with(Attribute::Underline);
+        attributes.underline = style;
+        attributes
+    }
+
+    /// Underline the text with a colour independent of the foreground.
+    pub fn with_underline_color(self, color: Color) -> Self {
+        let mut attributes = self.with(Attribute::Underline);
+        attributes.underline_color = Some(color);
+        attributes
+    }
+
+    pub fn underline_style(&self) -> Option<UnderlineStyle> {
+        match self.contains(Attribute::Underline) {
+            true => Some(self.underline),
+            false => None,
+        }
+    }
+
+    pub fn underline_color(&self) -> Option<Color> {
+        self.underline_color
+    }
+
+    /// Attributes as drawn by a terminal without underline styles and
+    /// colours, a plain underline.
+    pub fn plain_underline(self) -> Self {
+        Self {
+            underline: UnderlineStyle::Single,
+            underline_color: None,
+            ..self
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Attribute> {
+        let attributes = *self;
+        Attribute::ALL
+            .iter()
+            .copied()
+            .filter(move |a| attributes.contains(*a))
+    }
+}
+
+impl From<Attribute> for Attributes {
+    fn from(attribute: Attribute) -> Self {
+        Attributes::empty().with(attribute)
+    }
+}
+
+impl std::ops::BitOr<Attribute> for Attributes {
+    type Output = Attributes;
+
+    fn bitor(self, attribute: Attribute) -> Self::Output {
+        self.with(attribute)
+    }
+}
+
+impl std::ops::BitOr for Attribute {
+    type Output = Attributes;
+
+    fn bitor(self, attribute: Attribute) -> Self::Output {
+        Attributes::from(self).with(attribute)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(unused)]
 pub enum Visibility {
@@ -40,35 +253,122 @@ pub enum Visibility {
     Show,
 }
 
+/// Mouse events reported by the terminal, each level includes the previous
+/// one.
+#[derive(Debug, Clone)]
+#[allow(unused)]
+pub enum MouseTracking {
+    /// Button presses, releases and the wheel (1000).
+    Click,
+    /// Motion while a button is pressed (1002).
+    Drag,
+    /// Any motion (1003).
+    Motion,
+}
+
 #[derive(Debug, Clone)]
 #[allow(unused)]
 pub enum Command {
+    /// Move to column x and row y, 0-based like the `Framebuffer`
+    /// coordinates.
     MoveTo(u16, u16),
+    MoveRight(u16),
+    MoveLeft(u16),
+    MoveUp(u16),
+    MoveDown(u16),
+    CarriageReturn,
     ApplyStyle(Style),
+    /// Apply several styles with a single SGR sequence.
+    ApplyStyles(Vec<Style>),
     Write(String),
     Cursor(Visibility),
     Clear,
+    EnableMouse(MouseTracking),
+    DisableMouse,
+    EnableBracketedPaste,
+    DisableBracketedPaste,
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+    /// Hold the screen updates until `EndSynchronizedUpdate`.
+    BeginSynchronizedUpdate,
+    EndSynchronizedUpdate,
+    /// DECRQM, the terminal replies with the state of a DEC private mode.
+    RequestMode(u16),
+    /// DA1, the terminal replies with its primary device attributes.
+    RequestPrimaryAttributes,
 }
 
 impl std::fmt::Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Command::MoveTo(x, y) => write!(f, "\x1B[{};{}H", x, y),
+            // CUP takes the row first and is 1-based.
+            Command::MoveTo(x, y) => write!(f, "\x1B[{};{}H", *y as u32 + 1, *x as u32 + 1),
+            Command::MoveRight(n) => write!(f, "\x1B[{}C", Count(*n)),
+            Command::MoveLeft(n) => write!(f, "\x1B[{}D", Count(*n)),
+            Command::MoveUp(n) => write!(f, "\x1B[{}A", Count(*n)),
+            Command::MoveDown(n) => write!(f, "\x1B[{}B", Count(*n)),
+            Command::CarriageReturn => write!(f, "\r"),
             Command::ApplyStyle(style) => write!(f, "{}", style),
+            Command::ApplyStyles(styles) => {
+                let params = styles.iter().map(Style::params).collect::<Vec<_>>();
+                write!(f, "\x1B[{}m", params.join(";"))
+            }
             Command::Write(s) => write!(f, "{}", s),
             Command::Cursor(visibility) => match visibility {
                 Visibility::Hidden => write!(f, "\x1B[?25l"),
                 Visibility::Show => write!(f, "\x1B[?25h"),
             },
             Command::Clear => write!(f, "\x1B[2J"),
+            Command::EnableMouse(tracking) => {
+                let mode = match tracking {
+                    MouseTracking::Click => 1000,
+                    MouseTracking::Drag => 1002,
+                    MouseTracking::Motion => 1003,
+                };
+
+                // SGR (1006) encoding, coordinates aren't limited to 223.
+                write!(f, "\x1B[?{}h\x1B[?1006h", mode)
+            }
+            Command::DisableMouse => {
+                write!(f, "\x1B[?1006l\x1B[?1003l\x1B[?1002l\x1B[?1000l")
+            }
+            Command::EnableBracketedPaste => write!(f, "\x1B[?2004h"),
+            Command::DisableBracketedPaste => write!(f, "\x1B[?2004l"),
+            Command::EnterAlternateScreen => write!(f, "\x1B[?1049h"),
+            Command::LeaveAlternateScreen => write!(f, "\x1B[?1049l"),
+            Command::BeginSynchronizedUpdate => write!(f, "\x1B[?{}h", SYNCHRONIZED_OUTPUT),
+            Command::EndSynchronizedUpdate => write!(f, "\x1B[?{}l", SYNCHRONIZED_OUTPUT),
+            Command::RequestMode(mode) => write!(f, "\x1B[?{}$p", mode),
+            Command::RequestPrimaryAttributes => write!(f, "\x1B[c"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Repeat count of a relative cursor movement, 1 is the default and is
+/// omitted.
+struct Count(u16);
+
+impl std::fmt::Display for Count {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            1 => Ok(()),
+            n => write!(f, "{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(unused)]
 pub enum Color {
-    Rgb { r: u8, g: u8, b: u8 },
+    Rgb {
+        r: u8,
+        g: u8,
+        b: u8,
+    },
+    /// Entry of the 256 colours palette.
+    Indexed(u8),
+    /// Colour configured as the default of the terminal.
+    Default,
     Black,
     Red,
     Green,
@@ -90,7 +390,9 @@ pub enum Color {
 impl Color {
     pub fn bg(&self) -> Cow<str> {
         match self {
-            Color::Rgb { r, g, b } => Cow::Owned(format!("{};{};{}", r, g, b)),
+            Color::Rgb { r, g, b } => Cow::Owned(format!("48;2;{};{};{}", r, g, b)),
+            Color::Indexed(n) => Cow::Owned(format!("48;5;{}", n)),
+            Color::Default => Cow::Borrowed("49"),
             Color::Black => Cow::Borrowed("40"),
             Color::Red => Cow::Borrowed("41"),
             Color::Green => Cow::Borrowed("42"),
@@ -110,9 +412,48 @@ impl Color {
         }
     }
 
+    /// Underline colour (SGR 58), named colours use their index in the
+    /// 256 colours palette.
+    pub fn underline(&self) -> Cow<str> {
+        match (self, self.index()) {
+            (Color::Rgb { r, g, b }, _) => Cow::Owned(format!("58;2;{};{};{}", r, g, b)),
+            (_, Some(index)) => Cow::Owned(format!("58;5;{}", index)),
+            (_, None) => Cow::Borrowed("59"),
+        }
+    }
+
+    /// Index of the colour in the 256 colours palette, the named colours are
+    /// its first 16 entries.
+    pub fn index(&self) -> Option<u8> {
+        let index = match self {
+            Color::Rgb { .. } | Color::Default => return None,
+            Color::Indexed(n) => *n,
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+        };
+
+        Some(index)
+    }
+
     pub fn fg(&self) -> Cow<str> {
         match self {
-            Color::Rgb { r, g, b } => Cow::Owned(format!("{};{};{}", r, g, b)),
+            Color::Rgb { r, g, b } => Cow::Owned(format!("38;2;{};{};{}", r, g, b)),
+            Color::Indexed(n) => Cow::Owned(format!("38;5;{}", n)),
+            Color::Default => Cow::Borrowed("39"),
             Color::Black => Cow::Borrowed("30"),
             Color::Red => Cow::Borrowed("31"),
             Color::Green => Cow::Borrowed("32"),
@@ -190,7 +531,255 @@ pub fn rgb(r: u8, g: u8, b: u8) -> Color {
     Color::Rgb { r, g, b }
 }
 
-pub fn configure<W: Write>(out: &mut W) -> std::io::Result<()> {
-    write!(out, "{}", Command::Clear)?;
-    Ok(())
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NAMED: [(Color, u8); 16] = [
+        (Color::Black, 0),
+        (Color::Red, 1),
+        (Color::Green, 2),
+        (Color::Yellow, 3),
+        (Color::Blue, 4),
+        (Color::Magenta, 5),
+        (Color::Cyan, 6),
+        (Color::White, 7),
+        (Color::BrightBlack, 60),
+        (Color::BrightRed, 61),
+        (Color::BrightGreen, 62),
+        (Color::BrightYellow, 63),
+        (Color::BrightBlue, 64),
+        (Color::BrightMagenta, 65),
+        (Color::BrightCyan, 66),
+        (Color::BrightWhite, 67),
+    ];
+
+    #[test]
+    fn named_colors() {
+        for (color, offset) in NAMED {
+            assert_eq!(
+                fg(color).to_string(),
+                format!("\x1B[{}m", 30 + offset as u16)
+            );
+            assert_eq!(
+                bg(color).to_string(),
+                format!("\x1B[{}m", 40 + offset as u16)
+            );
+        }
+    }
+
+    #[test]
+    fn rgb_colors() {
+        assert_eq!(fg(rgb(1, 22, 255)).to_string(), "\x1B[38;2;1;22;255m");
+        assert_eq!(bg(rgb(1, 22, 255)).to_string(), "\x1B[48;2;1;22;255m");
+        assert_eq!(fg(rgb(0, 0, 0)).to_string(), "\x1B[38;2;0;0;0m");
+        assert_eq!(bg(rgb(255, 255, 255)).to_string(), "\x1B[48;2;255;255;255m");
+    }
+
+    #[test]
+    fn attributes() {
+        let cases = [
+            (Attribute::Bold, "1", "22"),
+            (Attribute::Dim, "2", "22"),
+            (Attribute::Italic, "3", "23"),
+            (Attribute::Underline, "4", "24"),
+            (Attribute::Reverse, "7", "27"),
+            (Attribute::Strikethrough, "9", "29"),
+        ];
+
+        for (attribute, on, off) in cases {
+            assert_eq!(
+                Style::Attribute(attribute).to_string(),
+                format!("\x1B[{}m", on)
+            );
+            assert_eq!(
+                Style::NoAttribute(attribute).to_string(),
+                format!("\x1B[{}m", off)
+            );
+        }
+    }
+
+    #[test]
+    fn attribute_set() {
+        let attributes = Attribute::Bold | Attribute::Underline;
+
+        assert!(attributes.contains(Attribute::Bold));
+        assert!(attributes.contains(Attribute::Underline));
+        assert!(!attributes.contains(Attribute::Dim));
+        assert_eq!(
+            attributes.iter().collect::<Vec<_>>(),
+            vec![Attribute::Bold, Attribute::Underline]
+        );
+        assert_eq!(
+            attributes.without(Attribute::Bold),
+            Attribute::Underline.into()
+        );
+        assert!(attributes
+            .without(Attribute::Bold)
+            .without(Attribute::Underline)
+            .is_empty());
+        assert_eq!(
+            Attributes::empty() | Attribute::Reverse,
+            Attribute::Reverse.into()
+        );
+    }
+
+    #[test]
+    fn underline_styles() {
+        let cases = [
+            (UnderlineStyle::Single, "\x1B[4m"),
+            (UnderlineStyle::Double, "\x1B[4:2m"),
+            (UnderlineStyle::Curly, "\x1B[4:3m"),
+            (UnderlineStyle::Dotted, "\x1B[4:4m"),
+            (UnderlineStyle::Dashed, "\x1B[4:5m"),
+        ];
+
+        for (style, expected) in cases {
+            assert_eq!(Style::Underline(style).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn underline_colors() {
+        assert_eq!(
+            Style::UnderlineColor(rgb(255, 0, 10)).to_string(),
+            "\x1B[58;2;255;0;10m"
+        );
+        assert_eq!(
+            Style::UnderlineColor(Color::Red).to_string(),
+            "\x1B[58;5;1m"
+        );
+        assert_eq!(
+            Style::UnderlineColor(Color::BrightWhite).to_string(),
+            "\x1B[58;5;15m"
+        );
+        assert_eq!(Style::NoUnderlineColor.to_string(), "\x1B[59m");
+    }
+
+    #[test]
+    fn underline_attributes() {
+        let attributes = Attributes::empty()
+            .with_underline(UnderlineStyle::Curly)
+            .with_underline_color(Color::Red);
+
+        assert!(attributes.contains(Attribute::Underline));
+        assert_eq!(attributes.underline_style(), Some(UnderlineStyle::Curly));
+        assert_eq!(attributes.underline_color(), Some(Color::Red));
+        assert_eq!(
+            attributes.plain_underline(),
+            Attributes::from(Attribute::Underline)
+        );
+        assert_eq!(
+            attributes.without(Attribute::Underline),
+            Attributes::empty()
+        );
+        assert_eq!(Attributes::empty().underline_style(), None);
+    }
+
+    #[test]
+    fn indexed_colors() {
+        for n in [0, 7, 16, 231, 255] {
+            assert_eq!(
+                fg(Color::Indexed(n)).to_string(),
+                format!("\x1B[38;5;{}m", n)
+            );
+            assert_eq!(
+                bg(Color::Indexed(n)).to_string(),
+                format!("\x1B[48;5;{}m", n)
+            );
+            assert_eq!(
+                Style::UnderlineColor(Color::Indexed(n)).to_string(),
+                format!("\x1B[58;5;{}m", n)
+            );
+        }
+    }
+
+    #[test]
+    fn default_colors() {
+        assert_eq!(fg(Color::Default).to_string(), "\x1B[39m");
+        assert_eq!(bg(Color::Default).to_string(), "\x1B[49m");
+        assert_eq!(
+            Style::UnderlineColor(Color::Default).to_string(),
+            "\x1B[59m"
+        );
+    }
+
+    #[test]
+    fn reset() {
+        assert_eq!(Style::Reset.to_string(), "\x1B[0m");
+    }
+
+    #[test]
+    fn apply_styles() {
+        assert_eq!(
+            Command::ApplyStyles(vec![Style::Reset, fg(Color::Red), bg(rgb(1, 2, 3))]).to_string(),
+            "\x1B[0;31;48;2;1;2;3m"
+        );
+        assert_eq!(
+            Command::ApplyStyle(fg(Color::BrightCyan)).to_string(),
+            "\x1B[96m"
+        );
+    }
+
+    #[test]
+    fn move_to_is_row_column_and_one_based() {
+        assert_eq!(Command::MoveTo(0, 0).to_string(), "\x1B[1;1H");
+        assert_eq!(Command::MoveTo(2, 5).to_string(), "\x1B[6;3H");
+        assert_eq!(Command::MoveTo(79, 23).to_string(), "\x1B[24;80H");
+        assert_eq!(
+            Command::MoveTo(u16::MAX, u16::MAX).to_string(),
+            "\x1B[65536;65536H"
+        );
+    }
+
+    #[test]
+    fn relative_moves() {
+        assert_eq!(Command::MoveRight(1).to_string(), "\x1B[C");
+        assert_eq!(Command::MoveRight(3).to_string(), "\x1B[3C");
+        assert_eq!(Command::MoveLeft(1).to_string(), "\x1B[D");
+        assert_eq!(Command::MoveLeft(12).to_string(), "\x1B[12D");
+        assert_eq!(Command::MoveUp(1).to_string(), "\x1B[A");
+        assert_eq!(Command::MoveUp(2).to_string(), "\x1B[2A");
+        assert_eq!(Command::MoveDown(1).to_string(), "\x1B[B");
+        assert_eq!(Command::MoveDown(40).to_string(), "\x1B[40B");
+        assert_eq!(Command::CarriageReturn.to_string(), "\r");
+    }
+
+    #[test]
+    fn screen_and_modes() {
+        let cases = [
+            (Command::Write(String::from("hé")), "hé"),
+            (Command::Cursor(Visibility::Hidden), "\x1B[?25l"),
+            (Command::Cursor(Visibility::Show), "\x1B[?25h"),
+            (Command::Clear, "\x1B[2J"),
+            (
+                Command::EnableMouse(MouseTracking::Click),
+                "\x1B[?1000h\x1B[?1006h",
+            ),
+            (
+                Command::EnableMouse(MouseTracking::Drag),
+                "\x1B[?1002h\x1B[?1006h",
+            ),
+            (
+                Command::EnableMouse(MouseTracking::Motion),
+                "\x1B[?1003h\x1B[?1006h",
+            ),
+            (
+                Command::DisableMouse,
+                "\x1B[?1006l\x1B[?1003l\x1B[?1002l\x1B[?1000l",
+            ),
+            (Command::EnableBracketedPaste, "\x1B[?2004h"),
+            (Command::DisableBracketedPaste, "\x1B[?2004l"),
+            (Command::EnterAlternateScreen, "\x1B[?1049h"),
+            (Command::LeaveAlternateScreen, "\x1B[?1049l"),
+            (Command::BeginSynchronizedUpdate, "\x1B[?2026h"),
+            (Command::EndSynchronizedUpdate, "\x1B[?2026l"),
+            (Command::RequestMode(2026), "\x1B[?2026$p"),
+            (Command::RequestPrimaryAttributes, "\x1B[c"),
+        ];
+
+        for (command, expected) in cases {
+            assert_eq!(command.to_string(), expected, "{:?}", command);
+        }
+    }
 }
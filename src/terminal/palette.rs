@@ -0,0 +1,240 @@
+use super::{Attributes, Color, Style};
+
+/// Colours a terminal can display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(unused)]
+pub enum ColorDepth {
+    /// No colours, only the default ones of the terminal.
+    Monochrome,
+    /// The 16 named colours.
+    Ansi16,
+    /// The 256 colours palette.
+    Indexed256,
+    /// 24-bit RGB.
+    #[default]
+    TrueColor,
+}
+
+/// The 16 named colours, as defined by xterm.
+const ANSI: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+    (Color::BrightBlack, (127, 127, 127)),
+    (Color::BrightRed, (255, 0, 0)),
+    (Color::BrightGreen, (0, 255, 0)),
+    (Color::BrightYellow, (255, 255, 0)),
+    (Color::BrightBlue, (92, 92, 255)),
+    (Color::BrightMagenta, (255, 0, 255)),
+    (Color::BrightCyan, (0, 255, 255)),
+    (Color::BrightWhite, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 colour cube, indexes 16 to 231.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// Nearest colour the terminal can display at `depth`.
+    pub fn downsample(self, depth: ColorDepth) -> Color {
+        match (depth, self) {
+            (_, Color::Default) => Color::Default,
+            (ColorDepth::Monochrome, _) => Color::Default,
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Indexed256, Color::Rgb { r, g, b }) => {
+                Color::Indexed(nearest_indexed((r, g, b)))
+            }
+            (ColorDepth::Indexed256, color) => color,
+            (ColorDepth::Ansi16, Color::Indexed(n)) if n < 16 => ANSI[n as usize].0,
+            (ColorDepth::Ansi16, Color::Indexed(n)) => nearest_ansi(indexed_rgb(n)),
+            (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => nearest_ansi((r, g, b)),
+            (ColorDepth::Ansi16, color) => color,
+        }
+    }
+}
+
+impl Style {
+    pub fn downsample(self, depth: ColorDepth) -> Style {
+        match self {
+            Style::Foreground(color) => Style::Foreground(color.downsample(depth)),
+            Style::Background(color) => Style::Background(color.downsample(depth)),
+            Style::UnderlineColor(color) => Style::UnderlineColor(color.downsample(depth)),
+            style => style,
+        }
+    }
+}
+
+impl Attributes {
+    pub fn downsample(mut self, depth: ColorDepth) -> Attributes {
+        self.underline_color = match depth {
+            ColorDepth::Monochrome => None,
+            depth => self.underline_color.map(|c| c.downsample(depth)),
+        };
+
+        self
+    }
+}
+
+/// RGB value of an entry of the 256 colours palette.
+fn indexed_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI[n as usize].1,
+        16..=231 => {
+            let n = n - 16;
+            (
+                CUBE[(n / 36) as usize],
+                CUBE[(n / 6 % 6) as usize],
+                CUBE[(n % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (n - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Nearest entry of the colour cube or of the grayscale ramp, the 16 first
+/// entries are skipped since their values depend on the terminal theme.
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&n| distance(rgb, indexed_rgb(n)))
+        .unwrap_or(16)
+}
+
+fn nearest_ansi(rgb: (u8, u8, u8)) -> Color {
+    ANSI.iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Default)
+}
+
+/// Squared "redmean" distance, a cheap approximation of the perceived
+/// difference between two colours.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let mean = (a.0 as i32 + b.0 as i32) / 2;
+    let r = a.0 as i32 - b.0 as i32;
+    let g = a.1 as i32 - b.1 as i32;
+    let b = a.2 as i32 - b.2 as i32;
+
+    ((((512 + mean) * r * r) >> 8) + 4 * g * g + (((767 - mean) * b * b) >> 8)) as u32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terminal::{rgb, Attribute};
+
+    #[test]
+    fn truecolor_is_unchanged() {
+        assert_eq!(rgb(1, 2, 3).downsample(ColorDepth::TrueColor), rgb(1, 2, 3));
+        assert_eq!(
+            Color::Indexed(42).downsample(ColorDepth::TrueColor),
+            Color::Indexed(42)
+        );
+    }
+
+    #[test]
+    fn rgb_to_indexed() {
+        let cases = [
+            (rgb(0, 0, 0), 16),
+            (rgb(255, 0, 0), 196),
+            (rgb(255, 255, 255), 231),
+            (rgb(0, 95, 135), 24),
+            (rgb(250, 130, 0), 208),
+            (rgb(128, 128, 128), 244),
+            (rgb(30, 30, 30), 234),
+            (rgb(238, 238, 238), 255),
+        ];
+
+        for (color, expected) in cases {
+            assert_eq!(
+                color.downsample(ColorDepth::Indexed256),
+                Color::Indexed(expected),
+                "{:?}",
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn to_ansi() {
+        let cases = [
+            (rgb(0, 0, 0), Color::Black),
+            (rgb(200, 10, 10), Color::Red),
+            (rgb(255, 20, 20), Color::BrightRed),
+            (rgb(250, 250, 250), Color::BrightWhite),
+            (rgb(120, 120, 130), Color::BrightBlack),
+            (rgb(10, 10, 230), Color::Blue),
+            (Color::Indexed(3), Color::Yellow),
+            (Color::Indexed(196), Color::BrightRed),
+            (Color::Indexed(232), Color::Black),
+            (Color::Cyan, Color::Cyan),
+        ];
+
+        for (color, expected) in cases {
+            assert_eq!(
+                color.downsample(ColorDepth::Ansi16),
+                expected,
+                "{:?}",
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn monochrome() {
+        assert_eq!(
+            rgb(1, 2, 3).downsample(ColorDepth::Monochrome),
+            Color::Default
+        );
+        assert_eq!(
+            Color::Red.downsample(ColorDepth::Monochrome),
+            Color::Default
+        );
+        assert_eq!(
+            Attributes::empty()
+                .with_underline_color(Color::Red)
+                .downsample(ColorDepth::Monochrome),
+            Attributes::from(Attribute::Underline)
+        );
+    }
+
+    #[test]
+    fn default_is_kept() {
+        for depth in [
+            ColorDepth::Monochrome,
+            ColorDepth::Ansi16,
+            ColorDepth::Indexed256,
+            ColorDepth::TrueColor,
+        ] {
+            assert_eq!(Color::Default.downsample(depth), Color::Default);
+        }
+    }
+
+    #[test]
+    fn rgb_to_indexed_is_perceptual() {
+        // The nearest level of each channel is (0, 95, 95), but the gray is
+        // closer.
+        assert_eq!(
+            rgb(10, 80, 50).downsample(ColorDepth::Indexed256),
+            Color::Indexed(237)
+        );
+        assert_eq!(
+            rgb(0, 34, 34).downsample(ColorDepth::Indexed256),
+            Color::Indexed(234)
+        );
+    }
+
+    #[test]
+    fn indexed_palette() {
+        assert_eq!(indexed_rgb(16), (0, 0, 0));
+        assert_eq!(indexed_rgb(196), (255, 0, 0));
+        assert_eq!(indexed_rgb(232), (8, 8, 8));
+        assert_eq!(indexed_rgb(255), (238, 238, 238));
+    }
+}
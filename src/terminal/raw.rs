@@ -0,0 +1,119 @@
+use std::{
+    fs::File,
+    io::Error,
+    os::fd::{AsRawFd, RawFd},
+    sync::{Mutex, Once},
+};
+
+use libc::{tcgetattr, tcsetattr, termios, TCSAFLUSH};
+
+use super::TTY;
+
+/// Attributes to restore from the panic hook, the hook runs before the stack
+/// unwinds so the panic message is printed on a sane terminal.
+static ORIGINAL: Mutex<Option<(RawFd, termios)>> = Mutex::new(None);
+static PANIC_HOOK: Once = Once::new();
+
+/// Put the tty in raw mode for the lifetime of the guard, the original
+/// attributes are restored on drop and on panic.
+pub struct RawMode {
+    tty: File,
+    original: termios,
+}
+
+#[allow(unused)]
+impl RawMode {
+    /// Enter raw mode, keeping signal generation so ^C and ^Z still work.
+    pub fn new() -> Result<Self, Error> {
+        Self::enable(true)
+    }
+
+    /// Enter raw mode and disable signal generation, ^C and ^Z are delivered
+    /// as regular input.
+    pub fn without_signals() -> Result<Self, Error> {
+        Self::enable(false)
+    }
+
+    fn enable(signals: bool) -> Result<Self, Error> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(TTY)?;
+        let fd = tty.as_raw_fd();
+
+        let original = get_attr(fd)?;
+        let mut raw = original;
+
+        raw.c_iflag &= !(libc::BRKINT | libc::ICRNL | libc::INPCK | libc::ISTRIP | libc::IXON);
+        raw.c_oflag &= !libc::OPOST;
+        raw.c_cflag |= libc::CS8;
+        raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::IEXTEN);
+        if !signals {
+            raw.c_lflag &= !libc::ISIG;
+        }
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+
+        set_attr(fd, &raw)?;
+
+        // Only once raw mode is on, on failure `tty` is dropped and the hook
+        // would restore a closed fd.
+        install_panic_hook();
+        *ORIGINAL.lock().unwrap_or_else(|e| e.into_inner()) = Some((fd, original));
+
+        Ok(Self { tty, original })
+    }
+
+    pub(super) fn fd(&self) -> RawFd {
+        self.tty.as_raw_fd()
+    }
+
+    pub(super) fn original(&self) -> termios {
+        self.original
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        ORIGINAL.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let _ = set_attr(self.tty.as_raw_fd(), &self.original);
+    }
+}
+
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some((fd, original)) = ORIGINAL.lock().unwrap_or_else(|e| e.into_inner()).take()
+            {
+                let _ = set_attr(fd, &original);
+            }
+
+            previous(info)
+        }));
+    });
+}
+
+fn get_attr(fd: RawFd) -> Result<termios, Error> {
+    let mut attr = std::mem::MaybeUninit::<termios>::uninit();
+
+    unsafe {
+        if tcgetattr(fd, attr.as_mut_ptr()) < 0 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(attr.assume_init())
+    }
+}
+
+/// Only calls `tcsetattr`, safe to use from a signal handler.
+pub(super) fn set_attr(fd: RawFd, attr: &termios) -> Result<(), Error> {
+    unsafe {
+        if tcsetattr(fd, TCSAFLUSH, attr) < 0 {
+            return Err(Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{
+    fs::File,
+    io::Error,
+    os::fd::AsRawFd,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use signal_hook::{consts::SIGWINCH, SigId};
+
+use super::{window_size_from, WinSize, TTY};
+
+/// Watch for SIGWINCH and report the new size of the terminal.
+///
+/// The signal handler only raises a flag, the size is queried when the
+/// application calls `poll`, usually once per frame.
+pub struct ResizeNotifier {
+    tty: File,
+    pending: Arc<AtomicBool>,
+    id: SigId,
+}
+
+#[allow(unused)]
+impl ResizeNotifier {
+    pub fn new() -> Result<Self, Error> {
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(TTY)?;
+
+        let pending = Arc::new(AtomicBool::new(false));
+        let id = signal_hook::flag::register(SIGWINCH, Arc::clone(&pending))?;
+
+        Ok(Self { tty, pending, id })
+    }
+
+    /// Return the new window size if the terminal was resized since the
+    /// last call.
+    pub fn poll(&self) -> Result<Option<WinSize>, Error> {
+        if !self.pending.swap(false, Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        window_size_from(self.tty.as_raw_fd()).map(Some)
+    }
+}
+
+impl Drop for ResizeNotifier {
+    fn drop(&mut self) {
+        signal_hook::low_level::unregister(self.id);
+    }
+}
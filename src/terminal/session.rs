@@ -0,0 +1,189 @@
+use std::{
+    io::Error,
+    os::fd::RawFd,
+    sync::{Mutex, Once},
+};
+
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    low_level, SigId,
+};
+
+use super::{raw::set_attr, Command, Escapes, RawMode, Style, Visibility};
+
+/// Tty to restore from the panic hook, with its restore sequence.
+static ACTIVE: Mutex<Option<(RawFd, Vec<u8>)>> = Mutex::new(None);
+static PANIC_HOOK: Once = Once::new();
+
+/// Signals that terminate the process, the screen is restored before the
+/// default action runs.
+const TERMINATING: [i32; 3] = [SIGTERM, SIGINT, SIGHUP];
+
+/// Full screen session on the tty, switch to the alternate screen and hide
+/// the cursor for the lifetime of the guard.
+///
+/// The main screen, the cursor and the SGR state are restored on drop, on
+/// panic and when the process is terminated by a signal, the raw mode guard
+/// is released last.
+///
+/// The sequences are encoded with `escapes`, the restore sequence is built
+/// once so the signal handlers only have to write it.
+pub struct Session {
+    raw: RawMode,
+    signals: Vec<SigId>,
+    restore: Vec<u8>,
+}
+
+impl Session {
+    pub fn new(raw: RawMode, escapes: &Escapes) -> Result<Self, Error> {
+        let fd = raw.fd();
+        let original = raw.original();
+        let restore = restore_sequence(escapes);
+
+        let mut signals = Vec::new();
+        for signal in TERMINATING {
+            let restore = restore.clone();
+            let action = move || {
+                unsafe {
+                    libc::write(fd, restore.as_ptr() as *const libc::c_void, restore.len());
+                }
+                let _ = set_attr(fd, &original);
+                let _ = low_level::emulate_default_handler(signal);
+            };
+
+            // The action only calls write(2), tcsetattr(3) and the default
+            // handler emulation, which are async-signal-safe.
+            let id = unsafe { low_level::register(signal, action)? };
+            signals.push(id);
+        }
+
+        install_panic_hook();
+        *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some((fd, restore.clone()));
+
+        let session = Self {
+            raw,
+            signals,
+            restore,
+        };
+        let enter = encode(
+            escapes,
+            &[
+                Command::EnterAlternateScreen,
+                Command::Clear,
+                Command::Cursor(Visibility::Hidden),
+            ],
+        );
+        write_all(fd, &enter)?;
+
+        Ok(session)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for id in self.signals.drain(..) {
+            low_level::unregister(id);
+        }
+
+        ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let _ = write_all(self.raw.fd(), &self.restore);
+    }
+}
+
+/// Sequence leaving every mode a session or the application could have
+/// enabled.
+fn restore_sequence(escapes: &Escapes) -> Vec<u8> {
+    encode(
+        escapes,
+        &[
+            Command::ApplyStyle(Style::Reset),
+            Command::Cursor(Visibility::Show),
+            Command::DisableMouse,
+            Command::DisableBracketedPaste,
+            Command::LeaveAlternateScreen,
+        ],
+    )
+}
+
+fn encode(escapes: &Escapes, commands: &[Command]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for command in commands {
+        // Writing to a Vec can't fail.
+        let _ = escapes.write(&mut bytes, command);
+    }
+
+    bytes
+}
+
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some((fd, restore)) = ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                let _ = write_all(fd, &restore);
+            }
+
+            previous(info)
+        }));
+    });
+}
+
+fn write_all(fd: RawFd, mut bytes: &[u8]) -> Result<(), Error> {
+    while !bytes.is_empty() {
+        let n = unsafe { libc::write(fd, bytes.as_ptr() as *const libc::c_void, bytes.len()) };
+
+        if n < 0 {
+            let err = Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+
+            return Err(err);
+        }
+
+        bytes = &bytes[n as usize..];
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terminal::terminfo::{cap, test_util::compile, Terminfo};
+
+    #[test]
+    fn restore_sequence_matches_commands() {
+        let expected = format!(
+            "{}{}{}{}{}",
+            Command::ApplyStyle(Style::Reset),
+            Command::Cursor(Visibility::Show),
+            Command::DisableMouse,
+            Command::DisableBracketedPaste,
+            Command::LeaveAlternateScreen,
+        );
+
+        assert_eq!(restore_sequence(&Escapes::default()), expected.as_bytes());
+    }
+
+    #[test]
+    fn restore_sequence_from_terminfo() {
+        let mut strings = vec![None; cap::EXIT_CA_MODE + 1];
+        strings[cap::EXIT_ATTRIBUTE_MODE] = Some(&b"<sgr0>"[..]);
+        strings[cap::CURSOR_NORMAL] = Some(&b"<cnorm>"[..]);
+        strings[cap::EXIT_CA_MODE] = Some(&b"<rmcup>"[..]);
+        let terminfo = Terminfo::parse(&compile("test", &[], &[], &strings)).unwrap();
+
+        let expected = format!(
+            "<sgr0><cnorm>{}{}<rmcup>",
+            Command::DisableMouse,
+            Command::DisableBracketedPaste,
+        );
+
+        assert_eq!(
+            restore_sequence(&Escapes::new(Some(terminfo))),
+            expected.as_bytes()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Indexes of the standard capabilities in a compiled entry, in the order
+//! of `term.h`.
+#![allow(unused)]
+
+// Numbers.
+pub const MAX_COLORS: usize = 13;
+
+// Strings.
+pub const CLEAR_SCREEN: usize = 5;
+pub const CURSOR_ADDRESS: usize = 10;
+pub const CURSOR_INVISIBLE: usize = 13;
+pub const CURSOR_NORMAL: usize = 16;
+pub const ENTER_CA_MODE: usize = 28;
+pub const EXIT_ATTRIBUTE_MODE: usize = 39;
+pub const EXIT_CA_MODE: usize = 40;
+pub const KEY_BACKSPACE: usize = 55;
+pub const KEY_DC: usize = 59;
+pub const KEY_DOWN: usize = 61;
+pub const KEY_F1: usize = 66;
+pub const KEY_F10: usize = 67;
+pub const KEY_F2: usize = 68;
+pub const KEY_F3: usize = 69;
+pub const KEY_F4: usize = 70;
+pub const KEY_F5: usize = 71;
+pub const KEY_F6: usize = 72;
+pub const KEY_F7: usize = 73;
+pub const KEY_F8: usize = 74;
+pub const KEY_F9: usize = 75;
+pub const KEY_HOME: usize = 76;
+pub const KEY_IC: usize = 77;
+pub const KEY_LEFT: usize = 79;
+pub const KEY_NPAGE: usize = 81;
+pub const KEY_PPAGE: usize = 82;
+pub const KEY_RIGHT: usize = 83;
+pub const KEY_UP: usize = 87;
+pub const KEY_BTAB: usize = 148;
+pub const KEY_END: usize = 164;
+pub const KEY_F11: usize = 216;
+pub const KEY_F12: usize = 217;
+pub const SET_A_FOREGROUND: usize = 359;
+pub const SET_A_BACKGROUND: usize = 360;
@@ -0,0 +1,653 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::input::Key;
+
+pub mod cap;
+mod param;
+
+pub use self::param::{expand, Param};
+
+/// Magic number of the legacy format, numbers are 16-bit.
+const MAGIC: u16 = 0o432;
+/// Magic number of the extended number format, numbers are 32-bit.
+const MAGIC_32: u16 = 0o1036;
+
+#[derive(Debug)]
+#[allow(unused)]
+pub enum TerminfoErr {
+    NotFound(String),
+    Reading(std::io::Error),
+    Invalid(&'static str),
+}
+
+impl std::error::Error for TerminfoErr {}
+
+impl std::fmt::Display for TerminfoErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerminfoErr::NotFound(term) => write!(f, "no terminfo entry for {}", term),
+            TerminfoErr::Reading(err) => write!(f, "can't read terminfo entry, error: {}", err),
+            TerminfoErr::Invalid(reason) => write!(f, "invalid terminfo entry: {}", reason),
+        }
+    }
+}
+
+impl From<std::io::Error> for TerminfoErr {
+    fn from(err: std::io::Error) -> Self {
+        TerminfoErr::Reading(err)
+    }
+}
+
+/// Compiled terminfo entry, as written by `tic`.
+#[derive(Debug, Clone, Default)]
+pub struct Terminfo {
+    names: Vec<String>,
+    booleans: Vec<bool>,
+    numbers: Vec<Option<i32>>,
+    strings: Vec<Option<Vec<u8>>>,
+    /// User-defined capabilities, like `Tc` or `Smulx`, by name.
+    extended: Extended,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Extended {
+    booleans: HashMap<String, bool>,
+    numbers: HashMap<String, i32>,
+    strings: HashMap<String, Vec<u8>>,
+}
+
+#[allow(unused)]
+impl Terminfo {
+    /// Load the entry of `$TERM`.
+    pub fn from_env() -> Result<Self, TerminfoErr> {
+        let term =
+            std::env::var("TERM").map_err(|_| TerminfoErr::NotFound(String::from("$TERM")))?;
+
+        Self::load(&term)
+    }
+
+    /// Load the entry of `term` from the standard search paths.
+    pub fn load(term: &str) -> Result<Self, TerminfoErr> {
+        Self::load_from(&search_paths(), term)
+    }
+
+    pub fn load_from(dirs: &[PathBuf], term: &str) -> Result<Self, TerminfoErr> {
+        let Some(first) = term.chars().next() else {
+            return Err(TerminfoErr::NotFound(String::from(term)));
+        };
+
+        for dir in dirs {
+            // Entries are stored under their first letter, or under its hex
+            // value on filesystems that are case insensitive.
+            let candidates = [
+                dir.join(first.to_string()).join(term),
+                dir.join(format!("{:x}", first as u32)).join(term),
+            ];
+
+            if let Some(path) = candidates.iter().find(|p| p.is_file()) {
+                return Self::parse(&std::fs::read(path)?);
+            }
+        }
+
+        Err(TerminfoErr::NotFound(String::from(term)))
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, TerminfoErr> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let number_size = match reader.u16()? {
+            MAGIC => 2,
+            MAGIC_32 => 4,
+            _ => return Err(TerminfoErr::Invalid("bad magic number")),
+        };
+        let names_size = reader.count()?;
+        let bool_count = reader.count()?;
+        let num_count = reader.count()?;
+        let str_count = reader.count()?;
+        let table_size = reader.count()?;
+
+        let names = reader.take(names_size)?;
+        let names = String::from_utf8_lossy(names.split(|&b| b == 0).next().unwrap_or(&[]))
+            .split('|')
+            .map(String::from)
+            .collect();
+
+        let booleans = reader.take(bool_count)?.iter().map(|&b| b == 1).collect();
+        reader.align()?;
+
+        let numbers = (0..num_count)
+            .map(|_| reader.number(number_size))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let offsets = (0..str_count)
+            .map(|_| reader.i16())
+            .collect::<Result<Vec<_>, _>>()?;
+        let table = reader.take(table_size)?;
+        let strings = offsets
+            .iter()
+            .map(|&offset| string_at(table, offset))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The extended capabilities follow, aligned, in entries compiled
+        // with them.
+        reader.align()?;
+        let extended = match reader.is_empty() {
+            true => Extended::default(),
+            false => Extended::parse(&mut reader, number_size)?,
+        };
+
+        Ok(Self {
+            names,
+            booleans,
+            numbers,
+            strings,
+            extended,
+        })
+    }
+
+    /// Names of the terminal, the first one is the one used to find the
+    /// entry.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn flag(&self, index: usize) -> bool {
+        self.booleans.get(index).copied().unwrap_or(false)
+    }
+
+    pub fn number(&self, index: usize) -> Option<i32> {
+        self.numbers.get(index).copied().flatten()
+    }
+
+    pub fn string(&self, index: usize) -> Option<&[u8]> {
+        self.strings.get(index)?.as_deref()
+    }
+
+    pub fn extended_flag(&self, name: &str) -> bool {
+        self.extended.booleans.get(name).copied().unwrap_or(false)
+    }
+
+    pub fn extended_number(&self, name: &str) -> Option<i32> {
+        self.extended.numbers.get(name).copied()
+    }
+
+    pub fn extended_string(&self, name: &str) -> Option<&[u8]> {
+        self.extended.strings.get(name).map(Vec::as_slice)
+    }
+
+    /// Expand the string capability at `index` with `params`, `None` if the
+    /// terminal doesn't have it or if it can't be evaluated.
+    pub fn expand(&self, index: usize, params: &[Param]) -> Option<Vec<u8>> {
+        expand(self.string(index)?, params).ok()
+    }
+
+    /// Expand the extended string capability `name` with `params`.
+    pub fn expand_extended(&self, name: &str, params: &[Param]) -> Option<Vec<u8>> {
+        expand(self.extended_string(name)?, params).ok()
+    }
+
+    /// Number of colours the terminal supports.
+    pub fn colors(&self) -> Option<i32> {
+        self.number(cap::MAX_COLORS)
+    }
+
+    /// Sequences sent by the terminal for the keys it defines.
+    pub fn keys(&self) -> Vec<(Vec<u8>, Key)> {
+        use self::cap::*;
+
+        let keys = [
+            (KEY_BACKSPACE, Key::Backspace),
+            (KEY_DC, Key::Delete),
+            (KEY_IC, Key::Insert),
+            (KEY_HOME, Key::Home),
+            (KEY_END, Key::End),
+            (KEY_PPAGE, Key::PageUp),
+            (KEY_NPAGE, Key::PageDown),
+            (KEY_UP, Key::Up),
+            (KEY_DOWN, Key::Down),
+            (KEY_LEFT, Key::Left),
+            (KEY_RIGHT, Key::Right),
+            (KEY_BTAB, Key::BackTab),
+            (KEY_F1, Key::F(1)),
+            (KEY_F2, Key::F(2)),
+            (KEY_F3, Key::F(3)),
+            (KEY_F4, Key::F(4)),
+            (KEY_F5, Key::F(5)),
+            (KEY_F6, Key::F(6)),
+            (KEY_F7, Key::F(7)),
+            (KEY_F8, Key::F(8)),
+            (KEY_F9, Key::F(9)),
+            (KEY_F10, Key::F(10)),
+            (KEY_F11, Key::F(11)),
+            (KEY_F12, Key::F(12)),
+        ];
+
+        keys.iter()
+            .filter_map(|&(index, key)| Some((self.string(index)?.to_vec(), key)))
+            .collect()
+    }
+}
+
+impl Extended {
+    /// Parse the extended section, the values are followed by the names of
+    /// all the capabilities in the same string table.
+    fn parse(reader: &mut Reader, number_size: usize) -> Result<Self, TerminfoErr> {
+        let bool_count = reader.count()?;
+        let num_count = reader.count()?;
+        let str_count = reader.count()?;
+        let _entries = reader.count()?;
+        let table_size = reader.count()?;
+
+        let booleans = reader.take(bool_count)?.to_vec();
+        reader.align()?;
+        let numbers = (0..num_count)
+            .map(|_| reader.number(number_size))
+            .collect::<Result<Vec<_>, _>>()?;
+        let offsets = (0..str_count)
+            .map(|_| reader.i16())
+            .collect::<Result<Vec<_>, _>>()?;
+        let name_offsets = (0..bool_count + num_count + str_count)
+            .map(|_| reader.i16())
+            .collect::<Result<Vec<_>, _>>()?;
+        let table = reader.take(table_size)?;
+
+        let values = offsets
+            .iter()
+            .map(|&offset| string_at(table, offset))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Names are offsets from the end of the values, stored one after
+        // the other.
+        let start = values.iter().flatten().map(|v| v.len() + 1).sum::<usize>();
+        let names = table
+            .get(start..)
+            .ok_or(TerminfoErr::Invalid("string offset out of the table"))?;
+        let mut names = name_offsets.iter().map(|&offset| {
+            string_at(names, offset)?
+                .map(|name| String::from_utf8_lossy(&name).into_owned())
+                .ok_or(TerminfoErr::Invalid("missing capability name"))
+        });
+
+        let mut extended = Extended::default();
+        for value in booleans {
+            extended.booleans.insert(names.next().unwrap()?, value == 1);
+        }
+        for value in numbers {
+            let name = names.next().unwrap()?;
+            if let Some(value) = value {
+                extended.numbers.insert(name, value);
+            }
+        }
+        for value in values {
+            let name = names.next().unwrap()?;
+            if let Some(value) = value {
+                extended.strings.insert(name, value);
+            }
+        }
+
+        Ok(extended)
+    }
+}
+
+/// Directories searched for compiled entries, in the order used by ncurses.
+fn search_paths() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = std::env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".terminfo"));
+    }
+    if let Ok(list) = std::env::var("TERMINFO_DIRS") {
+        dirs.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+
+    dirs.extend(
+        [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/lib/terminfo",
+            "/usr/local/share/terminfo",
+        ]
+        .iter()
+        .map(PathBuf::from),
+    );
+
+    dirs
+}
+
+/// NUL terminated string at `offset` in the string table, negative offsets
+/// are absent (-1) or cancelled (-2) capabilities.
+fn string_at(table: &[u8], offset: i16) -> Result<Option<Vec<u8>>, TerminfoErr> {
+    if offset < 0 {
+        return Ok(None);
+    }
+
+    let rest = table
+        .get(offset as usize..)
+        .ok_or(TerminfoErr::Invalid("string offset out of the table"))?;
+    let end = rest
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(TerminfoErr::Invalid("unterminated string"))?;
+
+    Ok(Some(rest[..end].to_vec()))
+}
+
+/// Little endian reader over a compiled entry.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], TerminfoErr> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + n)
+            .ok_or(TerminfoErr::Invalid("truncated entry"))?;
+        self.pos += n;
+
+        Ok(bytes)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn u16(&mut self) -> Result<u16, TerminfoErr> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, TerminfoErr> {
+        self.u16().map(|n| n as i16)
+    }
+
+    /// Size or count from a header, -1 is used for empty sections.
+    fn count(&mut self) -> Result<usize, TerminfoErr> {
+        Ok(self.i16()?.max(0) as usize)
+    }
+
+    /// Number capability, negative values are absent or cancelled.
+    fn number(&mut self, size: usize) -> Result<Option<i32>, TerminfoErr> {
+        let n = match size {
+            2 => self.i16()? as i32,
+            _ => {
+                let b = self.take(4)?;
+                i32::from_le_bytes([b[0], b[1], b[2], b[3]])
+            }
+        };
+
+        Ok(if n < 0 { None } else { Some(n) })
+    }
+
+    /// Skip the padding byte aligning the next section on an even offset.
+    fn align(&mut self) -> Result<(), TerminfoErr> {
+        if self.pos % 2 == 1 && !self.is_empty() {
+            self.take(1)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+
+    /// Compile an entry in the legacy format.
+    pub fn compile(
+        names: &str,
+        booleans: &[bool],
+        numbers: &[i16],
+        strings: &[Option<&[u8]>],
+    ) -> Vec<u8> {
+        let mut table = Vec::new();
+        let offsets = strings
+            .iter()
+            .map(|s| match s {
+                Some(s) => {
+                    let offset = table.len() as i16;
+                    table.extend_from_slice(s);
+                    table.push(0);
+                    offset
+                }
+                None => -1,
+            })
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        for n in [
+            MAGIC as i16,
+            names.len() as i16 + 1,
+            booleans.len() as i16,
+            numbers.len() as i16,
+            strings.len() as i16,
+            table.len() as i16,
+        ] {
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(names.as_bytes());
+        bytes.push(0);
+        bytes.extend(booleans.iter().map(|&b| b as u8));
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        for n in numbers {
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+        for offset in offsets {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        bytes.extend(table);
+
+        bytes
+    }
+
+    /// Append extended boolean and string capabilities to a compiled
+    /// entry.
+    pub fn extend(
+        mut bytes: Vec<u8>,
+        booleans: &[(&str, bool)],
+        strings: &[(&str, &[u8])],
+    ) -> Vec<u8> {
+        let mut table = Vec::new();
+        let mut offsets = Vec::new();
+        for (_, value) in strings {
+            offsets.push(table.len() as i16);
+            table.extend_from_slice(value);
+            table.push(0);
+        }
+
+        let names_start = table.len();
+        let mut name_offsets = Vec::new();
+        for name in booleans
+            .iter()
+            .map(|b| b.0)
+            .chain(strings.iter().map(|s| s.0))
+        {
+            name_offsets.push((table.len() - names_start) as i16);
+            table.extend_from_slice(name.as_bytes());
+            table.push(0);
+        }
+
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        for n in [
+            booleans.len() as i16,
+            0,
+            strings.len() as i16,
+            (strings.len() + name_offsets.len()) as i16,
+            table.len() as i16,
+        ] {
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+        bytes.extend(booleans.iter().map(|b| b.1 as u8));
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        for offset in offsets.iter().chain(name_offsets.iter()) {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        bytes.extend(table);
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::{compile, extend};
+    use super::*;
+
+    fn strings(entries: &[(usize, &'static [u8])]) -> Vec<Option<&'static [u8]>> {
+        let len = entries.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+        let mut strings = vec![None; len];
+        for (i, s) in entries {
+            strings[*i] = Some(*s);
+        }
+
+        strings
+    }
+
+    #[test]
+    fn parse_legacy_entry() {
+        let mut numbers = vec![-1; 15];
+        numbers[0] = 80;
+        numbers[cap::MAX_COLORS] = 256;
+
+        let bytes = compile(
+            "xterm-test|test terminal",
+            &[true, false, true],
+            &numbers,
+            &strings(&[
+                (cap::CLEAR_SCREEN, b"\x1B[H\x1B[2J"),
+                (cap::CURSOR_ADDRESS, b"\x1B[%i%p1%d;%p2%dH"),
+            ]),
+        );
+
+        let info = Terminfo::parse(&bytes).unwrap();
+
+        assert_eq!(info.names(), ["xterm-test", "test terminal"]);
+        assert!(info.flag(0));
+        assert!(!info.flag(1));
+        assert!(info.flag(2));
+        assert!(!info.flag(100));
+        assert_eq!(info.number(0), Some(80));
+        assert_eq!(info.number(1), None);
+        assert_eq!(info.colors(), Some(256));
+        assert_eq!(info.string(cap::CLEAR_SCREEN), Some(&b"\x1B[H\x1B[2J"[..]));
+        assert_eq!(
+            info.string(cap::CURSOR_ADDRESS),
+            Some(&b"\x1B[%i%p1%d;%p2%dH"[..])
+        );
+        assert_eq!(info.string(cap::CURSOR_INVISIBLE), None);
+        assert_eq!(info.string(1000), None);
+    }
+
+    #[test]
+    fn parse_32_bit_numbers() {
+        let mut bytes = Vec::new();
+        for n in [MAGIC_32 as i16, 2, 0, 1, 0, 0] {
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"t\0");
+        bytes.extend_from_slice(&0x1000000i32.to_le_bytes());
+
+        let info = Terminfo::parse(&bytes).unwrap();
+
+        assert_eq!(info.number(0), Some(0x1000000));
+    }
+
+    #[test]
+    fn invalid_entries() {
+        assert!(matches!(
+            Terminfo::parse(b"\x00\x00"),
+            Err(TerminfoErr::Invalid(_))
+        ));
+
+        let bytes = compile("t", &[], &[], &strings(&[(0, b"abc")]));
+        assert!(matches!(
+            Terminfo::parse(&bytes[..bytes.len() - 2]),
+            Err(TerminfoErr::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn parse_extended_capabilities() {
+        let bytes = compile("t", &[true], &[8], &strings(&[(0, b"\x07")]));
+        let bytes = extend(
+            bytes,
+            &[("Tc", true), ("AX", false)],
+            &[("Ss", b"\x1B[%p1%d q"), ("Se", b"\x1B[2 q")],
+        );
+
+        let info = Terminfo::parse(&bytes).unwrap();
+
+        assert!(info.flag(0));
+        assert_eq!(info.number(0), Some(8));
+        assert_eq!(info.string(0), Some(&b"\x07"[..]));
+        assert!(info.extended_flag("Tc"));
+        assert!(!info.extended_flag("AX"));
+        assert!(!info.extended_flag("RGB"));
+        assert_eq!(info.extended_number("U8"), None);
+        assert_eq!(info.extended_string("Se"), Some(&b"\x1B[2 q"[..]));
+        assert_eq!(
+            info.expand_extended("Ss", &[Param::Number(5)]),
+            Some(b"\x1B[5 q".to_vec())
+        );
+        assert_eq!(info.expand_extended("Smulx", &[]), None);
+    }
+
+    #[test]
+    fn keys() {
+        let bytes = compile(
+            "t",
+            &[],
+            &[],
+            &strings(&[
+                (cap::KEY_UP, b"\x1BOA"),
+                (cap::KEY_F12, b"\x1B[24~"),
+                (cap::KEY_BACKSPACE, b"\x7F"),
+            ]),
+        );
+
+        let info = Terminfo::parse(&bytes).unwrap();
+
+        assert_eq!(
+            info.keys(),
+            vec![
+                (b"\x7F".to_vec(), Key::Backspace),
+                (b"\x1BOA".to_vec(), Key::Up),
+                (b"\x1B[24~".to_vec(), Key::F(12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_from_directories() {
+        let dir = std::env::temp_dir().join(format!("paranormal-terminfo-{}", std::process::id()));
+        let entry = dir.join("x");
+        std::fs::create_dir_all(&entry).unwrap();
+        std::fs::write(
+            entry.join("xterm-test"),
+            compile("xterm-test", &[], &[], &[]),
+        )
+        .unwrap();
+
+        let info = Terminfo::load_from(&[PathBuf::from("/nonexistent"), dir.clone()], "xterm-test");
+        let missing = Terminfo::load_from(&[dir.clone()], "missing");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(info.unwrap().names(), ["xterm-test"]);
+        assert!(matches!(missing, Err(TerminfoErr::NotFound(_))));
+    }
+}
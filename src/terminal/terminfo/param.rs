@@ -0,0 +1,425 @@
+use super::TerminfoErr;
+
+/// Largest width or precision of a conversion, capabilities only need a few
+/// columns and a corrupt entry shouldn't allocate much more.
+const MAX_WIDTH: usize = 256;
+
+/// Parameter of a capability string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(unused)]
+pub enum Param {
+    Number(i32),
+    Str(Vec<u8>),
+}
+
+impl Param {
+    fn number(&self) -> i32 {
+        match self {
+            Param::Number(n) => *n,
+            Param::Str(_) => 0,
+        }
+    }
+}
+
+/// Evaluate the parameterized string `cap` with `params`, as `tparm` does.
+///
+/// Padding (`$<n>`) is dropped, it only matters to terminals at very low
+/// baud rates. Static variables (`%P[A-Z]`) don't persist across calls.
+pub fn expand(cap: &[u8], params: &[Param]) -> Result<Vec<u8>, TerminfoErr> {
+    let mut params = params.to_vec();
+    params.resize(9, Param::Number(0));
+
+    let mut out = Vec::new();
+    let mut stack = Vec::new();
+    let mut vars = vec![Param::Number(0); 52];
+    let mut i = 0;
+
+    while i < cap.len() {
+        let b = cap[i];
+        i += 1;
+
+        if b == b'$' && cap.get(i) == Some(&b'<') {
+            if let Some(end) = cap[i..].iter().position(|&b| b == b'>') {
+                i += end + 1;
+                continue;
+            }
+        }
+
+        if b != b'%' {
+            out.push(b);
+            continue;
+        }
+
+        let op = *cap
+            .get(i)
+            .ok_or(TerminfoErr::Invalid("truncated operator"))?;
+        i += 1;
+
+        match op {
+            b'%' => out.push(b'%'),
+            b'c' => out.push(pop(&mut stack).number() as u8),
+            b'p' => {
+                let n = digit(cap.get(i), b'1', b'9')?;
+                i += 1;
+                stack.push(params[n].clone());
+            }
+            b'P' | b'g' => {
+                let name = *cap.get(i).ok_or(TerminfoErr::Invalid("missing variable"))?;
+                i += 1;
+                let var = match name {
+                    b'a'..=b'z' => (name - b'a') as usize,
+                    b'A'..=b'Z' => (name - b'A') as usize + 26,
+                    _ => return Err(TerminfoErr::Invalid("bad variable name")),
+                };
+                match op {
+                    b'P' => vars[var] = pop(&mut stack),
+                    _ => stack.push(vars[var].clone()),
+                }
+            }
+            b'\'' => {
+                let c = *cap
+                    .get(i)
+                    .ok_or(TerminfoErr::Invalid("missing character"))?;
+                if cap.get(i + 1) != Some(&b'\'') {
+                    return Err(TerminfoErr::Invalid("unterminated character"));
+                }
+                i += 2;
+                stack.push(Param::Number(c as i32));
+            }
+            b'{' => {
+                let len = cap[i..]
+                    .iter()
+                    .position(|&b| b == b'}')
+                    .ok_or(TerminfoErr::Invalid("unterminated constant"))?;
+                let n = std::str::from_utf8(&cap[i..i + len])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or(TerminfoErr::Invalid("bad constant"))?;
+                i += len + 1;
+                stack.push(Param::Number(n));
+            }
+            b'l' => {
+                let len = match pop(&mut stack) {
+                    Param::Str(s) => s.len() as i32,
+                    Param::Number(_) => 0,
+                };
+                stack.push(Param::Number(len));
+            }
+            b'+' | b'-' | b'*' | b'/' | b'm' | b'&' | b'|' | b'^' | b'=' | b'<' | b'>' | b'A'
+            | b'O' => {
+                let b = pop(&mut stack).number();
+                let a = pop(&mut stack).number();
+                stack.push(Param::Number(binary(op, a, b)));
+            }
+            b'!' => {
+                let a = pop(&mut stack).number();
+                stack.push(Param::Number((a == 0) as i32));
+            }
+            b'~' => {
+                let a = pop(&mut stack).number();
+                stack.push(Param::Number(!a));
+            }
+            b'i' => {
+                for param in params.iter_mut().take(2) {
+                    if let Param::Number(n) = param {
+                        *n += 1;
+                    }
+                }
+            }
+            b'?' | b';' => {}
+            b't' => {
+                if pop(&mut stack).number() == 0 {
+                    i = skip(cap, i, true);
+                }
+            }
+            // Reached at the end of a `then` branch that was taken.
+            b'e' => i = skip(cap, i, false),
+            _ => {
+                let (format, len) = Format::parse(&cap[i - 1..])?;
+                i += len - 1;
+                format.write(&mut out, pop(&mut stack));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn pop(stack: &mut Vec<Param>) -> Param {
+    stack.pop().unwrap_or(Param::Number(0))
+}
+
+fn digit(b: Option<&u8>, min: u8, max: u8) -> Result<usize, TerminfoErr> {
+    match b {
+        Some(&b) if (min..=max).contains(&b) => Ok((b - min) as usize),
+        _ => Err(TerminfoErr::Invalid("bad parameter number")),
+    }
+}
+
+fn binary(op: u8, a: i32, b: i32) -> i32 {
+    match op {
+        b'+' => a.wrapping_add(b),
+        b'-' => a.wrapping_sub(b),
+        b'*' => a.wrapping_mul(b),
+        b'/' => a.checked_div(b).unwrap_or(0),
+        b'm' => a.checked_rem(b).unwrap_or(0),
+        b'&' => a & b,
+        b'|' => a | b,
+        b'^' => a ^ b,
+        b'=' => (a == b) as i32,
+        b'<' => (a < b) as i32,
+        b'>' => (a > b) as i32,
+        b'A' => (a != 0 && b != 0) as i32,
+        _ => (a != 0 || b != 0) as i32,
+    }
+}
+
+/// Position after the branch starting at `i`: after the matching `%e` when
+/// `to_else` is set and there is one, after the matching `%;` otherwise.
+fn skip(cap: &[u8], mut i: usize, to_else: bool) -> usize {
+    let mut depth = 0;
+
+    while i + 1 < cap.len() {
+        if cap[i] != b'%' {
+            i += 1;
+            continue;
+        }
+
+        i += 2;
+        match cap[i - 1] {
+            b'?' => depth += 1,
+            b';' if depth == 0 => return i,
+            b';' => depth -= 1,
+            b'e' if depth == 0 && to_else => return i,
+            _ => {}
+        }
+    }
+
+    cap.len()
+}
+
+/// printf-like conversion, `%[[:]flags][width[.precision]][doxXs]`.
+#[derive(Debug, Default)]
+struct Format {
+    left: bool,
+    zero: bool,
+    sign: bool,
+    space: bool,
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: u8,
+}
+
+impl Format {
+    /// Parse the conversion at the start of `spec`, returns it with its
+    /// length.
+    fn parse(spec: &[u8]) -> Result<(Self, usize), TerminfoErr> {
+        let mut format = Format::default();
+        let mut i = 0;
+
+        // A `:` allows `-` and `+` to be used as flags instead of operators.
+        if spec.first() == Some(&b':') {
+            i += 1;
+        }
+        while let Some(&b) = spec.get(i) {
+            match b {
+                b'-' => format.left = true,
+                b'0' => format.zero = true,
+                b'+' => format.sign = true,
+                b' ' => format.space = true,
+                b'#' => format.alternate = true,
+                _ => break,
+            }
+            i += 1;
+        }
+
+        let (width, len) = number(&spec[i..])?;
+        format.width = width;
+        i += len;
+        if spec.get(i) == Some(&b'.') {
+            let (precision, len) = number(&spec[i + 1..])?;
+            format.precision = Some(precision);
+            i += len + 1;
+        }
+
+        match spec.get(i) {
+            Some(&c @ (b'd' | b'o' | b'x' | b'X' | b's')) => format.conversion = c,
+            _ => return Err(TerminfoErr::Invalid("unknown operator")),
+        }
+
+        Ok((format, i + 1))
+    }
+
+    fn write(&self, out: &mut Vec<u8>, param: Param) {
+        let mut text = match (self.conversion, param) {
+            (b's', Param::Str(mut s)) => {
+                if let Some(precision) = self.precision {
+                    s.truncate(precision);
+                }
+                s
+            }
+            (b's', Param::Number(n)) => n.to_string().into_bytes(),
+            (_, param) => self.integer(param.number()).into_bytes(),
+        };
+
+        let padding = vec![b' '; self.width.saturating_sub(text.len())];
+        match self.left {
+            true => text.extend(padding),
+            false => text.splice(0..0, padding).for_each(drop),
+        }
+
+        out.extend(text);
+    }
+
+    fn integer(&self, n: i32) -> String {
+        let digits = match self.conversion {
+            b'o' => format!("{:o}", n.unsigned_abs()),
+            b'x' => format!("{:x}", n.unsigned_abs()),
+            b'X' => format!("{:X}", n.unsigned_abs()),
+            _ => n.unsigned_abs().to_string(),
+        };
+        let mut zeros = self.precision.unwrap_or(0).saturating_sub(digits.len());
+
+        let prefix = match (self.conversion, self.alternate && n != 0) {
+            (b'o', true) if zeros == 0 => "0",
+            (b'x', true) => "0x",
+            (b'X', true) => "0X",
+            _ => "",
+        };
+        let sign = match (n < 0, self.sign, self.space) {
+            (true, _, _) => "-",
+            (false, true, _) if self.conversion == b'd' => "+",
+            (false, false, true) if self.conversion == b'd' => " ",
+            _ => "",
+        };
+
+        // Zero padding up to the width, ignored with a precision.
+        if self.zero && !self.left && self.precision.is_none() {
+            let len = sign.len() + prefix.len() + digits.len();
+            zeros = self.width.saturating_sub(len);
+        }
+
+        format!("{}{}{}{}", sign, prefix, "0".repeat(zeros), digits)
+    }
+}
+
+/// Decimal width or precision at the start of `bytes`, with its length.
+fn number(bytes: &[u8]) -> Result<(usize, usize), TerminfoErr> {
+    let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    let n = bytes[..len]
+        .iter()
+        .try_fold(0usize, |n, b| {
+            n.checked_mul(10)?.checked_add((b - b'0') as usize)
+        })
+        .filter(|&n| n <= MAX_WIDTH)
+        .ok_or(TerminfoErr::Invalid("width too large"))?;
+
+    Ok((n, len))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn numbers(cap: &str, params: &[i32]) -> String {
+        let params = params.iter().map(|&n| Param::Number(n)).collect::<Vec<_>>();
+        String::from_utf8(expand(cap.as_bytes(), &params).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn cursor_address() {
+        assert_eq!(numbers("\x1B[%i%p1%d;%p2%dH", &[4, 9]), "\x1B[5;10H");
+        assert_eq!(numbers("\x1B[%i%p1%d;%p2%dH", &[0, 0]), "\x1B[1;1H");
+    }
+
+    #[test]
+    fn xterm_256_colors() {
+        let setaf = "\x1B[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m";
+
+        assert_eq!(numbers(setaf, &[1]), "\x1B[31m");
+        assert_eq!(numbers(setaf, &[9]), "\x1B[91m");
+        assert_eq!(numbers(setaf, &[200]), "\x1B[38;5;200m");
+    }
+
+    #[test]
+    fn direct_colors() {
+        let setaf = "\x1B[%?%p1%{8}%<%t3%p1%d%e38:2::%p1%{65536}%/%d:%p1%{256}%/%{255}%&%d:%p1%{255}%&%d%;m";
+
+        assert_eq!(numbers(setaf, &[0x123456]), "\x1B[38:2::18:52:86m");
+        assert_eq!(numbers(setaf, &[3]), "\x1B[33m");
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let cap = "%?%p1%t%?%p2%ta%eb%;%ec%;!";
+
+        assert_eq!(numbers(cap, &[1, 1]), "a!");
+        assert_eq!(numbers(cap, &[1, 0]), "b!");
+        assert_eq!(numbers(cap, &[0, 1]), "c!");
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(numbers("%p1%p2%+%d", &[3, 4]), "7");
+        assert_eq!(numbers("%p1%p2%-%d", &[3, 4]), "-1");
+        assert_eq!(numbers("%p1%p2%*%d", &[3, 4]), "12");
+        assert_eq!(numbers("%p1%p2%/%d", &[9, 0]), "0");
+        assert_eq!(numbers("%p1%p2%m%d", &[9, 4]), "1");
+        assert_eq!(numbers("%p1%p2%&%p1%p2%|%p1%p2%^%d%d%d", &[6, 3]), "572");
+        assert_eq!(numbers("%p1%p2%=%p1%p2%<%p1%p2%>%d%d%d", &[1, 2]), "010");
+        assert_eq!(numbers("%p1%p2%A%p1%p2%O%d%d", &[1, 0]), "10");
+        assert_eq!(numbers("%p1%!%p1%~%d%d", &[0]), "-11");
+    }
+
+    #[test]
+    fn constants_and_variables() {
+        assert_eq!(numbers("%'A'%c%{42}%d", &[]), "A42");
+        assert_eq!(numbers("%p1%Pa%p2%PZ%gZ%ga%d%d", &[1, 2]), "12");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            numbers("%p1%3d|%p1%:-3d|%p1%03d|%p1%.3d", &[7]),
+            "  7|7  |007|007"
+        );
+        assert_eq!(numbers("%p2%05d|%p2%:-05d", &[0, -7]), "-0007|-7   ");
+        assert_eq!(
+            numbers("%p1%x|%p1%X|%p1%#x|%p2%o|%p2%#o", &[255, 8]),
+            "ff|FF|0xff|10|010"
+        );
+        assert_eq!(numbers("%p1%:+d|%p2% d|%p3%d", &[1, 2, -3]), "+1| 2|-3");
+        assert_eq!(numbers("%%%p1%d", &[5]), "%5");
+    }
+
+    #[test]
+    fn strings() {
+        let params = [Param::Str(b"title".to_vec())];
+
+        assert_eq!(expand(b"[%p1%s]", &params).unwrap(), b"[title]");
+        assert_eq!(expand(b"[%p1%.2s]", &params).unwrap(), b"[ti]");
+        assert_eq!(expand(b"%p1%l%d", &params).unwrap(), b"5");
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(numbers("\x1B[H$<5>\x1B[2J$<2*/>", &[]), "\x1B[H\x1B[2J");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(expand(b"%p0%d", &[]).is_err());
+        assert!(expand(b"%q", &[]).is_err());
+        assert!(expand(b"%{12", &[]).is_err());
+        assert!(expand(b"%", &[]).is_err());
+    }
+
+    #[test]
+    fn width_bounds() {
+        assert_eq!(numbers("%256d", &[1]).len(), 256);
+        assert!(expand(b"%p1%257d", &[Param::Number(1)]).is_err());
+        assert!(expand(b"%p1%.1000d", &[Param::Number(1)]).is_err());
+        assert!(expand(b"%p1%99999999999999999999999d", &[Param::Number(1)]).is_err());
+    }
+}